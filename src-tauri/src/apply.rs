@@ -1,160 +1,161 @@
 // Import necessary components
-use std::process::Command;
+use crate::check::has_shell_theme;
+use crate::cursors::apply_cursor_theme;
+use crate::desktop::{accent_hex, detect_backend, parse_accent, report, summarize, ApplyError};
+use crate::flatpak::apply_overrides;
+use crate::libadwaita::apply_libadwaita;
+use crate::paths::find_theme_dir;
+use crate::plasma::{asset_label, installed_plasma_assets};
+use crate::qt::{apply_kvantum, apply_qt_colors, installed_kvantum_themes, pick_kvantum_theme};
+use crate::registry::{find_installed, installed_manifest, load_registry};
+use crate::settings::load_settings;
+use crate::snapshots::take_snapshot;
+use crate::types::{ApplyReport, ColorScheme, ComponentReport, ComponentStatus, InstallScope, InstalledTheme, ThemeManifest};
 
-#[tauri::command]
-pub fn apply_theme(theme_name: String) -> Result<String, String> {
-    let mut results = Vec::new();
-    let mut warnings = Vec::new();
-    
-    // Apply GTK theme using gsettings
-    let gtk_output = Command::new("gsettings")
-        .arg("set")
-        .arg("org.gnome.desktop.interface")
-        .arg("gtk-theme")
-        .arg(&theme_name)
-        .output()
-        .map_err(|e| format!("Failed to execute gsettings for GTK theme: {}", e))?;
-    
-    if gtk_output.status.success() {
-        results.push("GTK theme".to_string());
-    } else {
-        // Throw error on failure
-        let error = String::from_utf8_lossy(&gtk_output.stderr);
-        warnings.push(format!("GTK theme failed: {}", error));
+// Component keys as recorded in the registry, "theme" covers GTK, window manager and shell
+pub const COMPONENTS: [&str; 6] = ["theme", "icons", "cursors", "fonts", "kde", "qt"];
+
+// The manifest of the applied theme, or of the installed theme that lists it as a variant
+fn theme_manifest(theme_name: &str, installed: &[InstalledTheme]) -> Option<ThemeManifest> {
+    installed.iter().find_map(installed_manifest).or_else(|| {
+        [InstallScope::User, InstallScope::System].into_iter()
+            .flat_map(load_registry)
+            .filter_map(|theme| installed_manifest(&theme))
+            .find(|manifest| manifest.variants.iter().any(|v| v.name == theme_name))
+    })
+}
+
+// Selected but not shipped by the theme, nothing went wrong
+fn missing(component: &str, theme_name: &str) -> ComponentReport {
+    ComponentReport {
+        component: component.to_string(),
+        status: ComponentStatus::Skipped,
+        message: Some(format!("{} doesn't include this component", theme_name)),
     }
-    
-    // Check if user-theme extension schema exists
-    let schema_check = Command::new("gsettings")
-        .arg("list-schemas")
-        .output()
-        .map_err(|e| format!("Failed to check schemas: {}", e))?;
-    
-    let schemas = String::from_utf8_lossy(&schema_check.stdout);
-    
-    if schemas.contains("org.gnome.shell.extensions.user-theme") {
-        // Apply Shell theme using gsettings
-        let shell_output = Command::new("gsettings")
-            .arg("set")
-            .arg("org.gnome.shell.extensions.user-theme")
-            .arg("name")
-            .arg(&theme_name)
-            .output()
-            .map_err(|e| format!("Failed to execute gsettings for Shell theme: {}", e))?;
-        
-        if shell_output.status.success() {
-            results.push("Shell theme".to_string());
-        } else {
-            let shell_error = String::from_utf8_lossy(&shell_output.stderr);
-            warnings.push(format!("Shell theme failed: {}", shell_error));
+}
+
+// Apply the selected components, or everything the theme installed when there is no selection.
+// The scheduler skips the snapshot, its switches would otherwise push the user's own look out of undo
+pub fn apply_components(theme_name: &str, selection: Option<&[String]>, snapshot: bool) -> Vec<ComponentReport> {
+    let installed = [InstallScope::User, InstallScope::System].into_iter()
+        .filter_map(|scope| find_installed(theme_name, scope))
+        .collect::<Vec<_>>();
+    let has_component = |component: &str| installed.iter()
+        .any(|t| t.components.iter().any(|c| c == component));
+    // Themes installed by hand aren't in the registry, treat them as GTK themes
+    let selected = |component: &str| match selection {
+        Some(selection) => selection.iter().any(|c| c == component),
+        None => has_component(component) || (component == "theme" && installed.is_empty()),
+    };
+
+    let manifest = theme_manifest(theme_name, &installed);
+    let variant = manifest.as_ref().and_then(|m| m.variants.iter().find(|v| v.name == theme_name));
+    let dark = variant.is_some_and(|v| v.color_scheme == ColorScheme::Dark);
+    let backend = detect_backend();
+    let mut reports = Vec::new();
+
+    // Keep the current look so the apply can be undone
+    if snapshot {
+        if let Err(e) = take_snapshot(backend.as_ref(), &format!("Before applying {}", theme_name)) {
+            reports.push(report("Snapshot", Err(ApplyError::Failed(e))));
+        }
+    }
+
+    if selected("theme") {
+        reports.push(report("GTK theme", backend.apply_gtk(theme_name)));
+        reports.push(report("Window manager theme", backend.apply_window_manager(theme_name)));
+        // gnome-shell/ and cinnamon/ themes, skipped for GTK-only themes found on disk
+        if find_theme_dir(theme_name).is_none_or(|dir| has_shell_theme(&dir)) {
+            reports.push(report("Shell theme", backend.apply_shell(theme_name)));
+        }
+        // GTK4 apps only follow the theme through ~/.config/gtk-4.0, so it's opt-in
+        if load_settings().libadwaita_theming {
+            reports.push(report("libadwaita", apply_libadwaita(theme_name).map_err(ApplyError::from)));
+        }
+
+        // Qt apps outside Plasma get the same colors through qt5ct/qt6ct
+        if load_settings().qt_color_schemes {
+            reports.push(report("Qt colors", apply_qt_colors(theme_name, dark).map_err(ApplyError::from)));
         }
-    } else {
-        // Try alternative methods to apply shell theme
-        let alt_result = try_alternative_shell_theme_methods(&theme_name);
-        match alt_result {
-            Ok(method) => results.push(format!("Shell theme (via {})", method)),
-            Err(e) => warnings.push(format!("Shell theme not applied: user-theme extension not found. Install it with: `gnome-extensions install user-theme@gnome-shell-extensions.gcampax.github.com` or via GNOME Extensions app. Error: {}", e))
+
+        // The variant decides light or dark, its accent wins over the theme's
+        if let Some(manifest) = &manifest {
+            if let Some(variant) = variant {
+                reports.push(report("Color scheme", backend.apply_color_scheme(variant.color_scheme)));
+            }
+            if let Some(accent) = variant.and_then(|v| v.accent_color.as_ref()).or(manifest.accent_color.as_ref()) {
+                let result = match parse_accent(accent) {
+                    Some(rgb) => backend.apply_accent_color(&accent_hex(rgb)),
+                    None => Err(ApplyError::Failed(format!("Invalid accent color: {}", accent))),
+                };
+                reports.push(report("Accent color", result));
+            }
         }
     }
-    
-    // Apply window manager theme (for other DEs)
-    let wm_result = apply_window_manager_theme(&theme_name);
-    match wm_result {
-        Ok(wm) => results.push(wm),
-        Err(_) => {} // Silently ignore WM theme failures as not all DEs support this
+
+    if selected("icons") {
+        if has_component("icons") {
+            reports.push(report("Icons", backend.apply_icons(theme_name)));
+        } else {
+            reports.push(missing("Icons", theme_name));
+        }
     }
-    
-    let mut message = String::new();
-    
-    if !results.is_empty() {
-        message.push_str(&format!("✅ Applied: {}\n", results.join(", ")));
+
+    // Cursors also get the toolkit-independent fallbacks
+    if selected("cursors") {
+        if has_component("cursors") {
+            reports.extend(apply_cursor_theme(theme_name, None));
+        } else {
+            reports.push(missing("Cursor", theme_name));
+        }
     }
-    
-    if !warnings.is_empty() {
-        message.push_str(&format!("⚠️  Warnings:\n{}", warnings.join("\n")));
+
+    // Font families come from the manifest, the files themselves were installed with the theme
+    if selected("fonts") {
+        match manifest.as_ref().and_then(|manifest| manifest.fonts.as_ref()) {
+            Some(fonts) => reports.push(report("Fonts", backend.apply_fonts(fonts))),
+            None => reports.push(missing("Fonts", theme_name)),
+        }
     }
-    
-    if results.is_empty() {
-        Err("Failed to apply any theme components".to_string())
-    } else {
-        Ok(message)
+
+    // Plasma assets are applied by name, the install recorded where each one went
+    if selected("kde") {
+        let paths = installed.iter().flat_map(|t| t.paths.clone()).collect::<Vec<_>>();
+        let assets = installed_plasma_assets(&paths);
+        if assets.is_empty() {
+            reports.push(missing("KDE Plasma assets", theme_name));
+        }
+        for (kind, name) in assets {
+            reports.push(report(asset_label(kind), backend.apply_plasma_asset(kind, &name)));
+        }
     }
-}
 
-// Try alternative methods to apply shell theme when user-theme extension isn't available
-fn try_alternative_shell_theme_methods(theme_name: &str) -> Result<String, String> {
-    let home_dir = std::env::var("HOME")
-        .map_err(|_| "Failed to get HOME directory".to_string())?;
-    
-    // Method 1: Try to enable user-theme extension first
-    let enable_output = Command::new("gnome-extensions")
-        .arg("enable")
-        .arg("user-theme@gnome-shell-extensions.gcampax.github.com")
-        .output();
-    
-    if let Ok(output) = enable_output {
-        if output.status.success() {
-            // Wait a moment for the extension to load
-            std::thread::sleep(std::time::Duration::from_millis(500));
-            
-            // Try applying the theme again
-            let shell_output = Command::new("gsettings")
-                .arg("set")
-                .arg("org.gnome.shell.extensions.user-theme")
-                .arg("name")
-                .arg(theme_name)
-                .output();
-                
-            if let Ok(result) = shell_output {
-                if result.status.success() {
-                    return Ok("auto-enabled user-theme extension".to_string());
-                }
-            }
+    if selected("qt") {
+        let paths = installed.iter().flat_map(|t| t.paths.clone()).collect::<Vec<_>>();
+        let themes = installed_kvantum_themes(&paths);
+        match pick_kvantum_theme(&themes, dark) {
+            Some(kvantum) => reports.push(report("Kvantum theme", apply_kvantum(kvantum).map_err(ApplyError::from))),
+            None => reports.push(missing("Kvantum theme", theme_name)),
         }
     }
-    
-    // Method 2: Try direct file manipulation (less reliable)
-    let dconf_path = format!("{}/.config/dconf/user", home_dir);
-    if std::path::Path::new(&dconf_path).exists() {
-        // This is more complex and risky, skip it for now
+
+    // Theme Flatpak apps as well when the user opted in
+    if load_settings().flatpak_integration && selected("theme") {
+        let system = installed.iter().any(|t| t.scope == InstallScope::System);
+        let result = apply_overrides(theme_name, system).map(|_| ()).map_err(ApplyError::from);
+        reports.push(report("Flatpak overrides", result));
     }
-    
-    Err("user-theme extension not available and auto-enable failed".to_string())
+
+    reports
 }
 
-// Apply window manager themes for non-GNOME desktop environments
-fn apply_window_manager_theme(theme_name: &str) -> Result<String, String> {
-    // Try XFCE
-    let xfce_output = Command::new("xfconf-query")
-        .arg("-c")
-        .arg("xfwm4")
-        .arg("-p")
-        .arg("/general/theme")
-        .arg("-s")
-        .arg(theme_name)
-        .output();
-    
-    if let Ok(output) = xfce_output {
-        if output.status.success() {
-            return Ok("XFCE window manager theme".to_string());
-        }
-    }
-    
-    // Try KDE
-    let kde_output = Command::new("kwriteconfig5")
-        .arg("--file")
-        .arg("kwinrc")
-        .arg("--group")
-        .arg("org.kde.kdecoration2")
-        .arg("--key")
-        .arg("theme")
-        .arg(theme_name)
-        .output();
-    
-    if let Ok(output) = kde_output {
-        if output.status.success() {
-            return Ok("KDE window manager theme".to_string());
-        }
+#[tauri::command]
+pub fn apply_theme(theme_name: String, components: Option<Vec<String>>) -> Result<ApplyReport, String> {
+    if let Some(unknown) = components.iter().flatten().find(|c| !COMPONENTS.contains(&c.as_str())) {
+        return Err(format!("Unknown component: {}", unknown));
     }
-    
-    Err("No compatible window manager found".to_string())
-}
\ No newline at end of file
+
+    let reports = apply_components(&theme_name, components.as_deref(), true);
+    let message = summarize(&reports, "Failed to apply any theme components")?;
+    Ok(ApplyReport { message, components: reports })
+}
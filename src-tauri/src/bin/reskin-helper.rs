@@ -0,0 +1,269 @@
+// Privileged helper for system-wide installs, run by reskin through pkexec.
+// Nothing passed in from the unprivileged side is trusted: theme names, source
+// directories and uninstall targets are all validated here.
+use std::fs::{self, File};
+use std::io;
+use std::os::unix::fs::{symlink, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+use serde::{Serialize, Deserialize};
+
+const THEMES_DIR: &str = "/usr/share/themes";
+const ICONS_DIR: &str = "/usr/share/icons";
+const FONTS_DIR: &str = "/usr/share/fonts/reskin";
+const REGISTRY_DIR: &str = "/var/lib/reskin";
+const REGISTRY_FILE: &str = "/var/lib/reskin/installed.json";
+
+// Same shape as types::InstalledTheme, kept separate so the helper stays self-contained
+#[derive(Serialize, Deserialize)]
+struct InstalledTheme {
+    name: String,
+    scope: String,
+    components: Vec<String>,
+    paths: Vec<String>,
+    installed_at: u64,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("install") if args.len() >= 4 => install(&args[1], &args[2], &args[3..]),
+        Some("uninstall") if args.len() == 2 => uninstall(&args[1]),
+        _ => Err("Usage: reskin-helper install <theme dir> <theme name> <component>... | uninstall <theme name>".to_string()),
+    };
+
+    match result {
+        Ok(message) => println!("{}", message),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// UID of the user who ran pkexec, refuse to run without it
+fn caller_uid() -> Result<u32, String> {
+    std::env::var("PKEXEC_UID")
+        .ok()
+        .and_then(|uid| uid.parse().ok())
+        .ok_or("reskin-helper must be run through pkexec".to_string())
+}
+
+// Theme names become directory names under /usr/share, so keep them boring
+fn validate_theme_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= 128
+        && !name.starts_with('.')
+        && !name.starts_with('-')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || " ._+-".contains(c));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid theme name '{}'", name))
+    }
+}
+
+// The source must be a real directory owned by the caller
+fn validate_source(source: &str, uid: u32) -> Result<PathBuf, String> {
+    let path = Path::new(source);
+    if !path.is_absolute() {
+        return Err("Theme directory must be an absolute path".to_string());
+    }
+
+    let meta = fs::symlink_metadata(path)
+        .map_err(|e| format!("Failed to read theme directory: {}", e))?;
+    if !meta.is_dir() {
+        return Err("Theme directory is not a directory".to_string());
+    }
+    if meta.uid() != uid {
+        return Err("Theme directory is not owned by the calling user".to_string());
+    }
+
+    Ok(path.to_path_buf())
+}
+
+fn component_dest(component: &str, theme_name: &str) -> Result<PathBuf, String> {
+    match component {
+        "theme" => Ok(Path::new(THEMES_DIR).join(theme_name)),
+        "icons" | "cursors" => Ok(Path::new(ICONS_DIR).join(theme_name)),
+        "fonts" => Ok(Path::new(FONTS_DIR).join(theme_name)),
+        _ => Err(format!("Unknown component '{}'", component)),
+    }
+}
+
+// Copy a tree without following symlinks. Symlinks are recreated as-is (icon
+// themes rely on them), and every regular file must belong to the caller so
+// the helper can't be used to publish files the caller couldn't read.
+// In fonts mode only font files are copied, and directories are created as fonts need them.
+fn copy_tree(src: &Path, dst: &Path, uid: u32, fonts_only: bool) -> Result<(), String> {
+    if !fonts_only {
+        create_dir(dst)?;
+    }
+
+    for entry in fs::read_dir(src).map_err(|e| format!("Failed to read {}: {}", src.display(), e))? {
+        let entry = entry.map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+        let meta = fs::symlink_metadata(&src_path)
+            .map_err(|e| format!("Failed to read {}: {}", src_path.display(), e))?;
+
+        if meta.file_type().is_symlink() {
+            if fonts_only {
+                continue;
+            }
+            let target = fs::read_link(&src_path)
+                .map_err(|e| format!("Failed to read link {}: {}", src_path.display(), e))?;
+            symlink(&target, &dst_path)
+                .map_err(|e| format!("Failed to create link {}: {}", dst_path.display(), e))?;
+        } else if meta.is_dir() {
+            copy_tree(&src_path, &dst_path, uid, fonts_only)?;
+        } else if meta.is_file() {
+            if fonts_only {
+                if !is_font(&src_path) {
+                    continue;
+                }
+                create_dir(dst)?;
+            }
+            copy_owned_file(&src_path, &dst_path, uid)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn create_dir(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    fs::set_permissions(dir, fs::Permissions::from_mode(0o755))
+        .map_err(|e| format!("Failed to set permissions on {}: {}", dir.display(), e))
+}
+
+// Same list as fonts::FONT_EXTENSIONS
+fn is_font(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ["ttf", "otf", "ttc", "otc"].contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+// Check ownership on the opened file so a swapped-in symlink can't slip through
+fn copy_owned_file(src: &Path, dst: &Path, uid: u32) -> Result<(), String> {
+    let mut input = File::open(src).map_err(|e| format!("Failed to open {}: {}", src.display(), e))?;
+    let meta = input.metadata().map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+    if !meta.is_file() || meta.uid() != uid {
+        return Err(format!("Refusing to copy {}: not a file owned by the calling user", src.display()));
+    }
+
+    let mut output = File::create(dst).map_err(|e| format!("Failed to create {}: {}", dst.display(), e))?;
+    io::copy(&mut input, &mut output).map_err(|e| format!("Failed to copy {}: {}", src.display(), e))?;
+    fs::set_permissions(dst, fs::Permissions::from_mode(0o644))
+        .map_err(|e| format!("Failed to set permissions on {}: {}", dst.display(), e))?;
+
+    Ok(())
+}
+
+fn load_registry() -> Vec<InstalledTheme> {
+    fs::read_to_string(REGISTRY_FILE)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_registry(themes: &[InstalledTheme]) -> Result<(), String> {
+    fs::create_dir_all(REGISTRY_DIR).map_err(|e| format!("Failed to create {}: {}", REGISTRY_DIR, e))?;
+    let json = serde_json::to_string_pretty(themes)
+        .map_err(|e| format!("Failed to serialize registry: {}", e))?;
+    fs::write(REGISTRY_FILE, json).map_err(|e| format!("Failed to write registry: {}", e))?;
+    fs::set_permissions(REGISTRY_FILE, fs::Permissions::from_mode(0o644))
+        .map_err(|e| format!("Failed to set registry permissions: {}", e))
+}
+
+fn install(source: &str, theme_name: &str, components: &[String]) -> Result<String, String> {
+    let uid = caller_uid()?;
+    validate_theme_name(theme_name)?;
+    let source = validate_source(source, uid)?;
+
+    let mut registry = load_registry();
+    // Only the exact directories the previous install recorded count as ours
+    let owned: Vec<String> = registry.iter()
+        .filter(|t| t.name == theme_name)
+        .flat_map(|t| t.paths.clone())
+        .collect();
+
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for component in components {
+        let dest = component_dest(component, theme_name)?;
+        if paths.contains(&dest) {
+            continue; // Icons and cursors share a directory
+        }
+
+        // Only replace directories reskin created itself
+        if dest.exists() {
+            if !owned.contains(&dest.to_string_lossy().to_string()) {
+                return Err(format!("{} already exists and was not installed by reskin", dest.display()));
+            }
+            fs::remove_dir_all(&dest).map_err(|e| format!("Failed to remove {}: {}", dest.display(), e))?;
+        }
+
+        copy_tree(&source, &dest, uid, component == "fonts")?;
+        paths.push(dest);
+    }
+
+    registry.retain(|t| t.name != theme_name);
+    registry.push(InstalledTheme {
+        name: theme_name.to_string(),
+        scope: "system".to_string(),
+        components: components.to_vec(),
+        paths: paths.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+        installed_at: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+    });
+    save_registry(&registry)?;
+
+    // Report cache refreshes on the following lines, one per cache
+    let mut message = format!("Theme '{}' installed system-wide", theme_name);
+    for dest in &paths {
+        if dest.starts_with(ICONS_DIR) {
+            message.push_str(&format!("\n{}", run_cache_tool("gtk-update-icon-cache", &["--force", "--ignore-theme-index", "--quiet"], dest, "Icon cache")));
+        } else if dest.starts_with(FONTS_DIR) {
+            message.push_str(&format!("\n{}", run_cache_tool("fc-cache", &["--force"], dest, "Font cache")));
+        }
+    }
+
+    Ok(message)
+}
+
+fn run_cache_tool(tool: &str, args: &[&str], dir: &Path, cache: &str) -> String {
+    match Command::new(tool).args(args).arg(dir).output() {
+        Ok(output) if output.status.success() => format!("✅ {} updated", cache),
+        Ok(output) => format!("⚠️ {} not refreshed: {}", cache, String::from_utf8_lossy(&output.stderr).trim()),
+        Err(e) => format!("⚠️ {} not refreshed: {}", cache, e),
+    }
+}
+
+fn uninstall(theme_name: &str) -> Result<String, String> {
+    caller_uid()?;
+    validate_theme_name(theme_name)?;
+
+    let mut registry = load_registry();
+    let entry = registry.iter()
+        .find(|t| t.name == theme_name)
+        .ok_or(format!("Theme '{}' was not installed system-wide by reskin", theme_name))?;
+
+    // Derive the paths again instead of trusting the registry contents
+    for component in &entry.components {
+        let dest = component_dest(component, theme_name)?;
+        if dest.exists() {
+            fs::remove_dir_all(&dest).map_err(|e| format!("Failed to remove {}: {}", dest.display(), e))?;
+        }
+    }
+
+    registry.retain(|t| t.name != theme_name);
+    save_registry(&registry)?;
+
+    Ok(format!("Theme '{}' uninstalled system-wide", theme_name))
+}
@@ -3,6 +3,10 @@ use std::path::Path;
 use crate::types::{BundleRequest};
 use std::fs::{self, File};
 use std::io::Write;
+use tauri::{AppHandle, State};
+use crate::jobs::JobManager;
+use crate::preview::{render_preview, PREVIEW_FILE};
+use crate::progress::Progress;
 
 #[tauri::command]
 #[allow(non_snake_case)] // Allow variables to be camelCase
@@ -13,12 +17,20 @@ pub fn _create_theme_dir(path: String) -> Result<String, String> {
 }
 
 
-#[tauri::command]
+#[tauri::command(async)]
 #[allow(non_snake_case)]  // Allow variables to be camelCase
-pub fn bundle_theme(request: BundleRequest) -> Result<String,String> {
+pub fn bundle_theme(app: AppHandle, jobs: State<'_, JobManager>, request: BundleRequest) -> Result<String,String> {
+    let mut progress = Progress::new(&app, &jobs, "bundle", &request.output_path);
+    let result = write_bundle(&request, &mut progress);
+    progress.finish(&result);
+    result
+}
+
+fn write_bundle(request: &BundleRequest, progress: &mut Progress) -> Result<String,String> {
     let magic = b"RSKN"; // Define the magic number
     let manifest_json = serde_json::to_string(&request.manifest) // Convert manifest to string
         .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    progress.track_cleanup(request.output_path.clone().into());
     let mut file = File::create(&request.output_path) // Create empty .reskin file
         .map_err(|e| format!("Failed to create file: {}", e))?;
     file.write_all(magic).map_err(|e| format!("Write error: {}", e))?; // Write magic number
@@ -28,9 +40,9 @@ pub fn bundle_theme(request: BundleRequest) -> Result<String,String> {
     file.write_all(manifest_json.as_bytes()) // Write manifest as bytes
         .map_err(|e| format!("Failed to write manifest data: {}", e))?;
 
-    // Write assets
-    for asset_path in &request.assets {
-        let full_path = if let Some(ref base_dir) = request.theme_directory {
+    // Resolve asset paths up front so the total size is known
+    let asset_paths: Vec<String> = request.assets.iter()
+        .map(|asset_path| if let Some(ref base_dir) = request.theme_directory {
             if Path::new(asset_path).is_absolute() {
                 asset_path.clone()
             } else {
@@ -38,8 +50,18 @@ pub fn bundle_theme(request: BundleRequest) -> Result<String,String> {
             }
         } else {
             asset_path.clone()
-        };
-        
+        })
+        .collect();
+    let total_bytes = asset_paths.iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|meta| meta.len())
+        .sum();
+    progress.phase("bundling", total_bytes, asset_paths.len() as u64);
+
+    // Write assets
+    for full_path in asset_paths {
+        progress.check_cancelled()?;
+        progress.set_file(&full_path);
         let asset_data = std::fs::read(&full_path)
             .map_err(|e| format!("Failed to read asset {}: {}", full_path, e))?;
         let filename = std::path::Path::new(&full_path)
@@ -59,14 +81,31 @@ pub fn bundle_theme(request: BundleRequest) -> Result<String,String> {
             .map_err(|e| format!("Failed to write asset length: {}", e))?;
         file.write_all(&asset_data) // Write asset data
             .map_err(|e| format!("Failed to write asset data: {}", e))?;
+        progress.add(asset_data.len() as u64, 1);
+    }
+
+    // Themes without a screenshot get a rendered one
+    let has_preview = request.assets.iter()
+        .any(|asset| Path::new(asset).file_name().is_some_and(|name| name == PREVIEW_FILE));
+    if let (true, false, Some(base_dir)) = (request.generate_preview, has_preview, &request.theme_directory) {
+        progress.phase("rendering preview", 0, 0);
+        let preview = render_preview(Path::new(base_dir), &request.manifest.name)?;
+        file.write_all(&(PREVIEW_FILE.len() as u32).to_le_bytes())
+            .map_err(|e| format!("Failed to write filename length: {}", e))?;
+        file.write_all(PREVIEW_FILE.as_bytes())
+            .map_err(|e| format!("Failed to write filename: {}", e))?;
+        file.write_all(&(preview.len() as u32).to_le_bytes())
+            .map_err(|e| format!("Failed to write asset length: {}", e))?;
+        file.write_all(&preview)
+            .map_err(|e| format!("Failed to write asset data: {}", e))?;
     }
 
     Ok("Bundle created successfully".to_string())
 }
 
-#[tauri::command]
+#[tauri::command(async)]
 #[allow(non_snake_case)] // Allow variables to be camelCase
-    pub fn bundle_theme_from_directory(request: BundleRequest) -> Result<String, String> {
+    pub fn bundle_theme_from_directory(app: AppHandle, jobs: State<'_, JobManager>, request: BundleRequest) -> Result<String, String> {
         
         let dir = match &request.theme_directory {
             Some(d) => d,
@@ -86,7 +125,7 @@ pub fn bundle_theme(request: BundleRequest) -> Result<String,String> {
             return Err("No files found in theme directory".to_string());
         }
         
-        bundle_theme(request)
+        bundle_theme(app, jobs, request)
     }
 
 // Helper function to collect files recursively from a directory
@@ -0,0 +1,67 @@
+// Refresh icon and font caches so new themes show up without logging out
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+pub fn command_exists(program: &str) -> bool {
+    Command::new("sh")
+        .arg("-c")
+        .arg(format!("command -v {}", program))
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+// Regenerate icon-theme.cache for an installed icon or cursor theme
+pub fn refresh_icon_cache(theme_dir: &Path) -> Result<String, String> {
+    let tool = ["gtk-update-icon-cache", "gtk4-update-icon-cache"]
+        .into_iter()
+        .find(|tool| command_exists(tool));
+
+    let cache_file = theme_dir.join("icon-theme.cache");
+    let tool = match tool {
+        Some(tool) => tool,
+        None => {
+            // A cache shipped inside the theme is stale after copying, and GTK
+            // prefers it over scanning, so drop it and let GTK scan the directories
+            if cache_file.exists() {
+                fs::remove_file(&cache_file)
+                    .map_err(|e| format!("Failed to remove stale icon cache: {}", e))?;
+            }
+            return Ok("Icon cache removed (gtk-update-icon-cache not found)".to_string());
+        }
+    };
+
+    let output = Command::new(tool)
+        .arg("--force")
+        .arg("--ignore-theme-index")
+        .arg("--quiet")
+        .arg(theme_dir)
+        .output()
+        .map_err(|e| format!("Failed to execute {}: {}", tool, e))?;
+
+    if output.status.success() {
+        Ok("Icon cache updated".to_string())
+    } else {
+        Err(format!("{} failed: {}", tool, String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+// Let fontconfig pick up fonts in a newly created directory
+pub fn refresh_font_cache(font_dir: &Path) -> Result<String, String> {
+    if !command_exists("fc-cache") {
+        return Err("fc-cache not found".to_string());
+    }
+
+    let output = Command::new("fc-cache")
+        .arg("--force")
+        .arg(font_dir)
+        .output()
+        .map_err(|e| format!("Failed to execute fc-cache: {}", e))?;
+
+    if output.status.success() {
+        Ok("Font cache updated".to_string())
+    } else {
+        Err(format!("fc-cache failed: {}", String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
@@ -1,4 +1,7 @@
-use std::fs;
+use crate::cursors::validate_cursor_dir;
+use crate::fonts::find_font_files;
+use crate::plasma::find_plasma_assets;
+use crate::qt::find_kvantum_themes;
 
 // Check if theme has GTK or window manager components
 pub fn has_gtk_or_wm_components(theme_path: &std::path::Path) -> bool {
@@ -8,6 +11,19 @@ pub fn has_gtk_or_wm_components(theme_path: &std::path::Path) -> bool {
     gtk_wm_dirs.iter().any(|dir| theme_path.join(dir).exists())
 }
 
+// Check if theme has a desktop shell theme
+pub fn has_shell_theme(theme_path: &std::path::Path) -> bool {
+    let shell_dirs = ["gnome-shell", "cinnamon"];
+    shell_dirs.iter().any(|dir| theme_path.join(dir).exists())
+}
+
+// Check if an icon directory is a real icon theme rather than a cursor theme or an Inherits= pointer
+pub fn is_icon_theme(theme_path: &std::path::Path) -> bool {
+    std::fs::read_to_string(theme_path.join("index.theme"))
+        .map(|content| content.lines().any(|line| line.trim_start().starts_with("Directories=")))
+        .unwrap_or(false)
+}
+
 // Check if theme has icons
 pub fn has_icons(theme_path: &std::path::Path) -> bool {
     // Look for common icon directories or files
@@ -19,22 +35,46 @@ pub fn has_icons(theme_path: &std::path::Path) -> bool {
     theme_path.join("index.theme").exists()
 }
 
-// Check if theme has cursors
+// Check if theme has cursors, at least one file in cursors/ must be a valid Xcursor
 pub fn has_cursors(theme_path: &std::path::Path) -> bool {
-    theme_path.join("cursors").exists() ||
-    theme_path.join("cursor.theme").exists()
+    validate_cursor_dir(theme_path).cursors > 0
 }
 
-// Check if theme has fonts
+// Check if theme has fonts, at any depth
 pub fn has_fonts(theme_path: &std::path::Path) -> bool {
-    if let Ok(entries) = fs::read_dir(theme_path) {
-        for entry in entries.flatten() {
-            if let Some(ext) = entry.path().extension() {
-                if ["ttf", "otf", "woff", "woff2", "eot"].contains(&ext.to_str().unwrap_or("")) {
-                    return true;
-                }
-            }
-        }
+    !find_font_files(theme_path).is_empty()
+}
+
+// Check if theme has KDE color schemes, Aurorae decorations, desktop themes or global themes
+pub fn has_plasma_assets(theme_path: &std::path::Path) -> bool {
+    !find_plasma_assets(theme_path).is_empty()
+}
+
+// Check if theme has Kvantum themes for Qt apps
+pub fn has_kvantum_themes(theme_path: &std::path::Path) -> bool {
+    !find_kvantum_themes(theme_path).is_empty()
+}
+
+// Component keys ("theme", "icons", "cursors", "fonts", "kde", "qt") found in a theme directory
+pub fn detect_components(theme_path: &std::path::Path) -> Vec<&'static str> {
+    let mut components = Vec::new();
+    if has_gtk_or_wm_components(theme_path) {
+        components.push("theme");
+    }
+    if has_icons(theme_path) {
+        components.push("icons");
+    }
+    if has_cursors(theme_path) {
+        components.push("cursors");
     }
-    false
-}
\ No newline at end of file
+    if has_fonts(theme_path) {
+        components.push("fonts");
+    }
+    if has_plasma_assets(theme_path) {
+        components.push("kde");
+    }
+    if has_kvantum_themes(theme_path) {
+        components.push("qt");
+    }
+    components
+}
@@ -0,0 +1,142 @@
+// Detect existing themes with the same name before installing over them
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::check::detect_components;
+use crate::paths::{work_dir, themes_dir_for, icons_dir_for, fonts_dir};
+use crate::registry::load_registry;
+use crate::types::{InstallLocation, InstallScope, ThemeConflict};
+use crate::utils::copy_dir_recursive;
+
+// Every directory a component with this name could already live in
+fn candidate_dirs(component: &str, theme_name: &str) -> Vec<PathBuf> {
+    let roots = match component {
+        "theme" => vec![
+            themes_dir_for(InstallLocation::Legacy),
+            themes_dir_for(InstallLocation::Xdg),
+            Ok(PathBuf::from("/usr/local/share/themes")),
+            Ok(PathBuf::from("/usr/share/themes")),
+        ],
+        "icons" | "cursors" => vec![
+            icons_dir_for(InstallLocation::Legacy),
+            icons_dir_for(InstallLocation::Xdg),
+            Ok(PathBuf::from("/usr/local/share/icons")),
+            Ok(PathBuf::from("/usr/share/icons")),
+        ],
+        "fonts" => vec![fonts_dir(), Ok(PathBuf::from("/usr/share/fonts/reskin"))],
+        _ => Vec::new(),
+    };
+
+    roots.into_iter().flatten().map(|root| root.join(theme_name)).collect()
+}
+
+// Where the privileged helper installs, it refuses to overwrite anything it didn't install
+const SYSTEM_ROOTS: [&str; 3] = ["/usr/share/themes", "/usr/share/icons", "/usr/share/fonts/reskin"];
+
+pub fn find_conflicts(theme_name: &str, components: &[&str], scope: InstallScope) -> Vec<ThemeConflict> {
+    let registry: Vec<_> = [InstallScope::User, InstallScope::System].into_iter()
+        .flat_map(load_registry)
+        .collect();
+
+    let mut conflicts: Vec<ThemeConflict> = Vec::new();
+    for component in components {
+        for dir in candidate_dirs(component, theme_name) {
+            let path = dir.to_string_lossy().to_string();
+            // Icons and cursors share a directory, report it once
+            if !dir.exists() || conflicts.iter().any(|c| c.path == path) {
+                continue;
+            }
+            let managed = registry.iter()
+                .any(|t| t.name == theme_name && t.paths.contains(&path));
+            let replaceable = managed || scope == InstallScope::User
+                || !SYSTEM_ROOTS.iter().any(|root| dir.starts_with(root));
+            conflicts.push(ThemeConflict { component: component.to_string(), path, managed, replaceable });
+        }
+    }
+    conflicts
+}
+
+// First "<name>-N" that nothing else uses
+pub fn unique_name(theme_name: &str, components: &[&str]) -> String {
+    (2..)
+        .map(|n| format!("{}-{}", theme_name, n))
+        .find(|candidate| find_conflicts(candidate, components, InstallScope::User).is_empty())
+        .unwrap()
+}
+
+// Point Name=, its translations and metatheme references to the old name at the new name
+fn rename_index_content(content: &str, old_name: &str, new_name: &str) -> String {
+    let rewritten: Vec<String> = content.lines()
+        .map(|line| match line.split_once('=') {
+            Some((key, _)) if key.trim() == "Name" || key.trim().starts_with("Name[") => {
+                format!("{}={}", key, new_name)
+            }
+            Some((key, value)) if value.trim() == old_name && key.trim().ends_with("Theme") => {
+                format!("{}={}", key, new_name)
+            }
+            _ => line.to_string(),
+        })
+        .collect();
+    rewritten.join("\n") + "\n"
+}
+
+fn rename_index_theme(theme_dir: &Path, old_name: &str, new_name: &str) -> Result<(), String> {
+    let index = theme_dir.join("index.theme");
+    let content = match fs::read_to_string(&index) {
+        Ok(content) => content,
+        Err(_) => return Ok(()), // Nothing to rewrite
+    };
+
+    fs::write(&index, rename_index_content(&content, old_name, new_name))
+        .map_err(|e| format!("Failed to rewrite index.theme: {}", e))
+}
+
+// Copy a theme into a temporary directory under its new name, returning the temp root and the copy
+pub fn stage_renamed(theme_path: &Path, old_name: &str, new_name: &str) -> Result<(PathBuf, PathBuf), String> {
+    let temp_root = work_dir("rename")?;
+    let _ = fs::remove_dir_all(&temp_root);
+    let staged = temp_root.join(new_name);
+
+    copy_dir_recursive(&theme_path.to_string_lossy(), &staged.to_string_lossy())
+        .map_err(|e| format!("Failed to stage renamed theme: {}", e))?;
+    rename_index_theme(&staged, old_name, new_name)?;
+
+    Ok((temp_root, staged))
+}
+
+#[tauri::command]
+pub fn check_install_conflicts(theme_path: String, scope: Option<InstallScope>) -> Result<Vec<ThemeConflict>, String> {
+    let path = Path::new(&theme_path);
+    if !path.exists() {
+        return Err(format!("Theme not found at '{}'", theme_path));
+    }
+    let theme_name = path
+        .file_name()
+        .ok_or("Invalid theme path")?
+        .to_string_lossy()
+        .to_string();
+
+    Ok(find_conflicts(&theme_name, &detect_components(path), scope.unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_every_name_entry() {
+        let content = "[Icon Theme]\nName=Nordic\nName[de]=Nordisch\nComment=Nordic icons\nInherits=Adwaita\n";
+        assert_eq!(
+            rename_index_content(content, "Nordic", "Nordic-2"),
+            "[Icon Theme]\nName=Nordic-2\nName[de]=Nordic-2\nComment=Nordic icons\nInherits=Adwaita\n"
+        );
+    }
+
+    #[test]
+    fn renames_metatheme_references() {
+        let content = "[X-GNOME-Metatheme]\nName=Nordic\nGtkTheme=Nordic\nIconTheme=Papirus\nCursorTheme=Nordic\n";
+        assert_eq!(
+            rename_index_content(content, "Nordic", "Nordic-2"),
+            "[X-GNOME-Metatheme]\nName=Nordic-2\nGtkTheme=Nordic-2\nIconTheme=Papirus\nCursorTheme=Nordic-2\n"
+        );
+    }
+}
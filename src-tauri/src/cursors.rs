@@ -0,0 +1,337 @@
+// Xcursor theme validation and cursor theme configuration
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::paths::{config_home, home_dir};
+use crate::desktop::{detect_backend, report, run, summarize, ApplyError, DEFAULT_CURSOR_SIZE};
+use crate::snapshots::take_snapshot;
+use crate::types::{ComponentReport, CursorReport};
+
+const XCURSOR_MAGIC: &[u8; 4] = b"Xcur";
+const XCURSOR_IMAGE_TYPE: u32 = 0xfffd0002;
+
+// Cursors every theme should provide, with the alternative names toolkits also look up
+const STANDARD_CURSORS: [&[&str]; 15] = [
+    &["left_ptr", "default", "arrow"],
+    &["text", "xterm", "ibeam"],
+    &["hand2", "pointer", "hand1"],
+    &["watch", "wait"],
+    &["left_ptr_watch", "progress"],
+    &["crosshair", "cross"],
+    &["fleur", "move", "all-scroll"],
+    &["sb_h_double_arrow", "ew-resize", "col-resize"],
+    &["sb_v_double_arrow", "ns-resize", "row-resize"],
+    &["top_left_corner", "nw-resize"],
+    &["top_right_corner", "ne-resize"],
+    &["bottom_left_corner", "sw-resize"],
+    &["bottom_right_corner", "se-resize"],
+    &["question_arrow", "help"],
+    &["not-allowed", "crossed_circle", "circle"],
+];
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+// Parse an Xcursor file and return the nominal sizes of its images
+pub fn parse_xcursor(data: &[u8]) -> Option<Vec<u32>> {
+    if data.get(0..4)? != XCURSOR_MAGIC {
+        return None;
+    }
+    let header_size = read_u32(data, 4)? as usize;
+    let toc_count = read_u32(data, 12)? as usize;
+
+    let mut sizes = Vec::new();
+    for i in 0..toc_count {
+        let toc = header_size + i * 12;
+        if read_u32(data, toc)? != XCURSOR_IMAGE_TYPE {
+            continue; // Comments and other chunks
+        }
+        let nominal = read_u32(data, toc + 4)?;
+        let position = read_u32(data, toc + 8)? as usize;
+
+        // The chunk header repeats type and size, followed by the image dimensions
+        if read_u32(data, position + 4)? != XCURSOR_IMAGE_TYPE {
+            return None;
+        }
+        let chunk_header = read_u32(data, position)? as usize;
+        let width = read_u32(data, position + 16)? as usize;
+        let height = read_u32(data, position + 20)? as usize;
+        if width == 0 || height == 0 || width > 0x7fff || height > 0x7fff {
+            return None;
+        }
+        let pixels_end = position + chunk_header + width * height * 4;
+        if pixels_end > data.len() {
+            return None;
+        }
+
+        if !sizes.contains(&nominal) {
+            sizes.push(nominal);
+        }
+    }
+
+    if sizes.is_empty() { None } else { Some(sizes) }
+}
+
+// Pixels of the image closest to the requested size, as (width, height, straight RGBA)
+pub fn read_xcursor_image(data: &[u8], size: u32) -> Option<(u32, u32, Vec<u8>)> {
+    parse_xcursor(data)?;
+    let header_size = read_u32(data, 4)? as usize;
+    let toc_count = read_u32(data, 12)? as usize;
+
+    let position = (0..toc_count)
+        .map(|i| header_size + i * 12)
+        .filter(|toc| read_u32(data, *toc) == Some(XCURSOR_IMAGE_TYPE))
+        .filter_map(|toc| Some((read_u32(data, toc + 4)?, read_u32(data, toc + 8)? as usize)))
+        .min_by_key(|(nominal, _)| nominal.abs_diff(size))
+        .map(|(_, position)| position)?;
+
+    let chunk_header = read_u32(data, position)? as usize;
+    let width = read_u32(data, position + 16)?;
+    let height = read_u32(data, position + 20)?;
+    let start = position + chunk_header;
+    let argb = data.get(start..start + (width * height * 4) as usize)?;
+
+    // Pixels are premultiplied ARGB in little-endian words
+    let mut rgba = Vec::with_capacity(argb.len());
+    for pixel in argb.chunks_exact(4) {
+        let (b, g, r, a) = (pixel[0] as u32, pixel[1] as u32, pixel[2] as u32, pixel[3] as u32);
+        let unpremultiply = |c: u32| (c * 255).checked_div(a).map_or(0, |v| v.min(255) as u8);
+        rgba.extend_from_slice(&[unpremultiply(r), unpremultiply(g), unpremultiply(b), a as u8]);
+    }
+    Some((width, height, rgba))
+}
+
+pub fn validate_cursor_dir(theme_path: &Path) -> CursorReport {
+    let cursors_dir = theme_path.join("cursors");
+    let mut names = Vec::new();
+    let mut sizes = Vec::new();
+    let mut invalid = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(&cursors_dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Symlinks are followed, most themes alias names to a handful of files
+            match fs::read(entry.path()).ok().as_deref().and_then(parse_xcursor) {
+                Some(file_sizes) => {
+                    for size in file_sizes {
+                        if !sizes.contains(&size) {
+                            sizes.push(size);
+                        }
+                    }
+                    names.push(name);
+                }
+                None => invalid.push(name),
+            }
+        }
+    }
+    sizes.sort_unstable();
+    invalid.sort();
+
+    let missing: Vec<String> = STANDARD_CURSORS.iter()
+        .filter(|aliases| !aliases.iter().any(|alias| names.iter().any(|n| n == alias)))
+        .map(|aliases| aliases[0].to_string())
+        .collect();
+
+    CursorReport {
+        valid: !names.is_empty() && missing.is_empty(),
+        cursors: names.len(),
+        sizes,
+        invalid,
+        missing,
+    }
+}
+
+#[tauri::command]
+pub fn validate_cursors(theme_path: String) -> Result<CursorReport, String> {
+    let path = Path::new(&theme_path);
+    if !path.join("cursors").is_dir() {
+        return Err(format!("No cursors directory in '{}'", theme_path));
+    }
+    Ok(validate_cursor_dir(path))
+}
+
+const DEFAULT_INDEX_MARKER: &str = "# Written by reskin";
+
+fn default_index_file() -> Result<PathBuf, String> {
+    Ok(home_dir()?.join(".icons/default/index.theme"))
+}
+
+fn xresources_file() -> Result<PathBuf, String> {
+    Ok(home_dir()?.join(".Xresources"))
+}
+
+fn cursor_environment_file() -> Result<PathBuf, String> {
+    Ok(config_home()?.join("environment.d/60-reskin-cursor.conf"))
+}
+
+// The fallbacks written next to the desktop's own setting, recorded by snapshots
+pub fn cursor_fallback_files() -> Vec<PathBuf> {
+    [default_index_file(), xresources_file(), cursor_environment_file()].into_iter()
+        .filter_map(Result::ok)
+        .collect()
+}
+
+// Files written before the marker existed still match the layout reskin used
+fn is_reskin_index(content: &str) -> bool {
+    let lines: Vec<&str> = content.lines().collect();
+    lines.contains(&DEFAULT_INDEX_MARKER)
+        || (lines.len() == 4 && lines[..3] == ["[Icon Theme]", "Name=Default", "Comment=Default Cursor Theme"]
+            && lines[3].starts_with("Inherits="))
+}
+
+// The default theme is what libXcursor falls back to for apps that ignore the DE settings,
+// an index.theme the user or another tool wrote is left alone
+fn write_default_index_theme(theme_name: &str) -> Result<(), String> {
+    let index = default_index_file()?;
+    if let Ok(existing) = fs::read_to_string(&index) {
+        if !is_reskin_index(&existing) {
+            return Err(format!("{} wasn't written by reskin, left unchanged", index.display()));
+        }
+    }
+    if let Some(default_dir) = index.parent() {
+        fs::create_dir_all(default_dir)
+            .map_err(|e| format!("Failed to create {}: {}", default_dir.display(), e))?;
+    }
+    let content = format!(
+        "{}\n[Icon Theme]\nName=Default\nComment=Default Cursor Theme\nInherits={}\n",
+        DEFAULT_INDEX_MARKER, theme_name
+    );
+    fs::write(index, content)
+        .map_err(|e| format!("Failed to write index.theme: {}", e))
+}
+
+// Replace the Xcursor lines in ~/.Xresources and merge them into the running X server
+fn write_xresources(theme_name: &str, size: u32) -> Result<(), String> {
+    let path = xresources_file()?;
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+
+    let mut lines: Vec<String> = existing.lines()
+        .filter(|line| !line.starts_with("Xcursor.theme:") && !line.starts_with("Xcursor.size:"))
+        .map(|line| line.to_string())
+        .collect();
+    lines.push(format!("Xcursor.theme: {}", theme_name));
+    lines.push(format!("Xcursor.size: {}", size));
+
+    fs::write(&path, lines.join("\n") + "\n")
+        .map_err(|e| format!("Failed to write .Xresources: {}", e))?;
+
+    if std::env::var("DISPLAY").is_ok() {
+        let _ = run("xrdb", &["-merge", &path.to_string_lossy()]);
+    }
+    Ok(())
+}
+
+// Wayland compositors read the cursor from the environment, systemd exports environment.d at login
+pub fn write_cursor_environment(theme_name: &str, size: u32) -> Result<(), String> {
+    let file = cursor_environment_file()?;
+    if let Some(env_dir) = file.parent() {
+        fs::create_dir_all(env_dir)
+            .map_err(|e| format!("Failed to create {}: {}", env_dir.display(), e))?;
+    }
+    let content = format!("XCURSOR_THEME={}\nXCURSOR_SIZE={}\n", theme_name, size);
+    fs::write(file, content)
+        .map_err(|e| format!("Failed to write cursor environment: {}", e))
+}
+
+// Set the cursor theme for the current desktop plus the toolkit-independent fallbacks
+pub fn apply_cursor_theme(theme_name: &str, size: Option<u32>) -> Vec<ComponentReport> {
+    let size = size.unwrap_or(DEFAULT_CURSOR_SIZE);
+    let backend = detect_backend();
+
+    vec![
+        report(&format!("{} cursor", backend.name()), backend.apply_cursor(theme_name, size)),
+        report("default cursor theme", write_default_index_theme(theme_name).map_err(ApplyError::from)),
+        report("Xresources cursor", write_xresources(theme_name, size).map_err(ApplyError::from)),
+        report("cursor environment", write_cursor_environment(theme_name, size).map_err(ApplyError::from)),
+    ]
+}
+
+#[tauri::command]
+pub fn set_cursor_theme(theme_name: String, size: Option<u32>) -> Result<String, String> {
+    take_snapshot(detect_backend().as_ref(), &format!("Before setting cursor {}", theme_name))?;
+    summarize(&apply_cursor_theme(&theme_name, size), "Failed to apply cursor theme")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMMENT_TYPE: u32 = 0xfffe0001;
+
+    fn image_chunk(size: u32, width: u32, height: u32) -> Vec<u8> {
+        let mut chunk = Vec::new();
+        for value in [36, XCURSOR_IMAGE_TYPE, size, 1, width, height, 0, 0, 0] {
+            chunk.extend(u32::to_le_bytes(value));
+        }
+        chunk.extend(vec![0xff; (width * height * 4) as usize]);
+        chunk
+    }
+
+    // (toc type, nominal size, chunk)
+    fn xcursor(entries: &[(u32, u32, Vec<u8>)]) -> Vec<u8> {
+        let mut data = XCURSOR_MAGIC.to_vec();
+        for value in [16, 0x10000, entries.len() as u32] {
+            data.extend(u32::to_le_bytes(value));
+        }
+        let mut position = 16 + entries.len() as u32 * 12;
+        for (kind, size, chunk) in entries {
+            for value in [*kind, *size, position] {
+                data.extend(u32::to_le_bytes(value));
+            }
+            position += chunk.len() as u32;
+        }
+        for (_, _, chunk) in entries {
+            data.extend(chunk);
+        }
+        data
+    }
+
+    #[test]
+    fn parses_nominal_sizes() {
+        let data = xcursor(&[
+            (XCURSOR_IMAGE_TYPE, 24, image_chunk(24, 24, 24)),
+            (COMMENT_TYPE, 1, vec![0; 20]),
+            (XCURSOR_IMAGE_TYPE, 48, image_chunk(48, 48, 48)),
+            // Animated cursors repeat the size for every frame
+            (XCURSOR_IMAGE_TYPE, 48, image_chunk(48, 48, 48)),
+        ]);
+        assert_eq!(parse_xcursor(&data), Some(vec![24, 48]));
+    }
+
+    #[test]
+    fn rejects_non_cursors() {
+        assert_eq!(parse_xcursor(b""), None);
+        assert_eq!(parse_xcursor(b"\x89PNG\r\n\x1a\n"), None);
+        assert_eq!(parse_xcursor(&xcursor(&[])), None);
+        assert_eq!(parse_xcursor(&xcursor(&[(COMMENT_TYPE, 1, vec![0; 20])])), None);
+    }
+
+    #[test]
+    fn rejects_broken_images() {
+        let valid = xcursor(&[(XCURSOR_IMAGE_TYPE, 24, image_chunk(24, 24, 24))]);
+        for len in 0..valid.len() {
+            assert_eq!(parse_xcursor(&valid[..len]), None, "truncated to {} bytes", len);
+        }
+
+        // TOC pointing at something that isn't an image chunk
+        let mut wrong_type = valid.clone();
+        wrong_type[28 + 4] = 0;
+        assert_eq!(parse_xcursor(&wrong_type), None);
+
+        assert_eq!(parse_xcursor(&xcursor(&[(XCURSOR_IMAGE_TYPE, 24, image_chunk(24, 0, 24))])), None);
+        assert_eq!(parse_xcursor(&xcursor(&[(XCURSOR_IMAGE_TYPE, 24, image_chunk(24, 0x8000, 1))])), None);
+
+        // More TOC entries than the file holds
+        let mut toc_count = valid;
+        toc_count[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(parse_xcursor(&toc_count), None);
+    }
+
+    #[test]
+    fn recognizes_reskin_index_theme() {
+        assert!(is_reskin_index("# Written by reskin\n[Icon Theme]\nName=Default\nComment=Default Cursor Theme\nInherits=Bibata\n"));
+        assert!(is_reskin_index("[Icon Theme]\nName=Default\nComment=Default Cursor Theme\nInherits=Bibata\n"));
+        assert!(!is_reskin_index("[Icon Theme]\nInherits=Adwaita\n"));
+        assert!(!is_reskin_index("[Icon Theme]\nName=Default\nComment=Default Cursor Theme\nInherits=Bibata\nDirectories=cursors\n"));
+    }
+}
@@ -0,0 +1,138 @@
+// reskin:// links from web pages: reskin://install/<themeId> or reskin://install?url=<bundle url>
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+use crate::extract::{extract_theme_info, is_plain_name};
+use crate::install::install_from_data;
+use crate::jobs::JobManager;
+use crate::marketplace::{fetch_bundle_url, fetch_theme_file, get_theme_info, save_downloaded_theme};
+use crate::progress::Progress;
+use crate::types::{ConflictResolution, InstallResult, InstallScope, InstallStatus, LinkPreview, ThemeManifest};
+
+pub const SCHEME: &str = "reskin://";
+
+pub enum DeepLink {
+    Marketplace(String), // Theme document id
+    Url(String),
+}
+
+// Link handed over on launch, held until the frontend asks for it
+#[derive(Default)]
+pub struct PendingLink(pub Mutex<Option<String>>);
+
+// Bundle fetched for a link, kept in memory until the user confirms the install
+#[derive(Default)]
+pub struct LinkDownload(Mutex<Option<(String, ThemeManifest, Vec<u8>)>>);
+
+pub fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                out.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+pub fn parse_deep_link(link: &str) -> Result<DeepLink, String> {
+    let rest = link.strip_prefix(SCHEME).ok_or(format!("Not a Reskin link: {}", link))?;
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let path = path.trim_end_matches('/');
+
+    if path == "install" {
+        let url = query.split('&')
+            .find_map(|pair| pair.strip_prefix("url="))
+            .map(percent_decode)
+            .ok_or("Link has no theme to install".to_string())?;
+        // Links come from arbitrary pages, only fetch over HTTPS
+        if !url.starts_with("https://") {
+            return Err("Only https:// theme URLs can be installed from links".to_string());
+        }
+        return Ok(DeepLink::Url(url));
+    }
+
+    match path.strip_prefix("install/") {
+        Some(id) if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.') => {
+            Ok(DeepLink::Marketplace(id.to_string()))
+        }
+        Some(_) => Err("Invalid theme id in link".to_string()),
+        None => Err(format!("Unsupported Reskin link: {}", link)),
+    }
+}
+
+#[tauri::command]
+pub fn take_pending_link(pending: State<'_, PendingLink>) -> Result<Option<String>, String> {
+    Ok(pending.0.lock().unwrap().take())
+}
+
+// Fetch the linked bundle into memory and return its manifest for confirmation
+#[tauri::command]
+pub async fn preview_deep_link(app: AppHandle, jobs: State<'_, JobManager>, downloads: State<'_, LinkDownload>, link: String) -> Result<LinkPreview, String> {
+    let mut progress = Progress::new(&app, &jobs, "download", &link);
+    let result = fetch_link(&link, &mut progress).await;
+    progress.finish(&result);
+    let (source, data) = result?;
+
+    let manifest = extract_theme_info(data.clone())?;
+    // Manifest names end up in file paths
+    if !is_plain_name(&manifest.name) {
+        return Err(format!("Invalid theme name: {}", manifest.name));
+    }
+
+    let preview = LinkPreview { link: link.clone(), source, manifest: manifest.clone(), size: data.len() as u64 };
+    *downloads.0.lock().unwrap() = Some((link, manifest, data));
+    Ok(preview)
+}
+
+async fn fetch_link(link: &str, progress: &mut Progress) -> Result<(String, Vec<u8>), String> {
+    match parse_deep_link(link)? {
+        DeepLink::Marketplace(theme_id) => {
+            let document = get_theme_info("reskin", "themes", &theme_id).await?;
+            let file_id = document["file"].as_str()
+                .ok_or(format!("Marketplace theme {} has no file", theme_id))?;
+            Ok((theme_id.clone(), fetch_theme_file(file_id, progress).await?))
+        }
+        DeepLink::Url(url) => {
+            let data = fetch_bundle_url(&url, progress).await?;
+            Ok((url, data))
+        }
+    }
+}
+
+// Save and install the previewed bundle once the user has confirmed it
+#[tauri::command(async)]
+#[allow(non_snake_case)]
+pub fn confirm_deep_link(app: AppHandle, jobs: State<'_, JobManager>, downloads: State<'_, LinkDownload>, link: String, autoApply: bool, scope: Option<InstallScope>, resolution: Option<ConflictResolution>) -> Result<InstallResult, String> {
+    let (manifest, data) = match downloads.0.lock().unwrap().take() {
+        Some((previewed, manifest, data)) if previewed == link => (manifest, data),
+        _ => return Err("This link hasn't been previewed, open it again".to_string()),
+    };
+
+    save_downloaded_theme(&manifest.name, &data)?;
+    let file_name = format!("{}.reskin", manifest.name);
+
+    let mut progress = Progress::new(&app, &jobs, "install", &manifest.name);
+    let result = install_from_data(&data, &file_name, autoApply, scope, resolution, &mut progress);
+    progress.finish(&result);
+
+    // Keep the bundle around while the user picks how to resolve a conflict
+    if matches!(&result, Ok(r) if r.status == InstallStatus::Conflict) {
+        *downloads.0.lock().unwrap() = Some((link, manifest, data));
+    }
+    result
+}
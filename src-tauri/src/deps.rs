@@ -0,0 +1,480 @@
+// Theme dependencies: version ranges, resolution against the registry and marketplace, install plans
+use std::collections::{HashMap, HashSet, VecDeque};
+use serde_json::Value;
+use crate::extract::extract_theme_info;
+use crate::install::install_from_data;
+use crate::marketplace::{fetch_bundle_url, fetch_marketplace_themes, fetch_theme_file};
+use crate::progress::Progress;
+use crate::registry::{find_installed, installed_manifest};
+use crate::types::{DependencyPlan, InstallScope, InstallStatus, MissingDependency, PlanStep, ThemeDependency, ThemeManifest};
+
+// Missing components count as 0, pre-release and build suffixes are ignored
+fn parse_version(version: &str) -> Option<[u64; 3]> {
+    let version = version.trim().trim_start_matches('v');
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = [0u64; 3];
+    for (i, part) in version.split('.').enumerate() {
+        if i >= 3 {
+            break;
+        }
+        parts[i] = part.parse().ok()?;
+    }
+    Some(parts)
+}
+
+// Number of components written in a requirement, "1.2" for ^1.2 is two
+fn precision(version: &str) -> usize {
+    version.trim().trim_start_matches('v').split('.').count().min(3)
+}
+
+fn matches_comparator(version: [u64; 3], comparator: &str) -> bool {
+    let comparator = comparator.trim();
+    if comparator.is_empty() || comparator == "*" {
+        return true;
+    }
+
+    let (op, required) = [">=", "<=", ">", "<", "=", "^", "~"]
+        .iter()
+        .find_map(|op| comparator.strip_prefix(op).map(|rest| (*op, rest)))
+        .unwrap_or(("^", comparator)); // A bare version behaves like a caret range, as in Cargo
+
+    let req = match parse_version(required) {
+        Some(req) => req,
+        None => return false,
+    };
+
+    // First version past everything the written components allow, "1.2" stops before 1.3.0
+    let next = |precision: usize| match precision {
+        1 => [req[0] + 1, 0, 0],
+        2 => [req[0], req[1] + 1, 0],
+        _ => [req[0], req[1], req[2] + 1],
+    };
+
+    match op {
+        ">=" => version >= req,
+        "<=" => version < next(precision(required)),
+        ">" => version >= next(precision(required)),
+        "<" => version < req,
+        "=" => version >= req && version < next(precision(required)),
+        "~" => version >= req && version < next(precision(required).min(2)),
+        _ => {
+            let upper = if req[0] > 0 || precision(required) == 1 {
+                [req[0] + 1, 0, 0]
+            } else if req[1] > 0 || precision(required) == 2 {
+                [0, req[1] + 1, 0]
+            } else {
+                [0, 0, req[2] + 1]
+            };
+            version >= req && version < upper
+        }
+    }
+}
+
+// Comparators are separated by commas, all of them must match
+pub fn version_matches(version: &str, range: Option<&str>) -> bool {
+    let range = match range {
+        Some(range) if !range.trim().is_empty() && range.trim() != "*" => range,
+        _ => return true,
+    };
+    match parse_version(version) {
+        Some(version) => range.split(',').all(|comparator| matches_comparator(version, comparator)),
+        None => false,
+    }
+}
+
+// Themes installed without a version only satisfy dependencies that don't ask for one
+fn satisfies(version: Option<&str>, range: Option<&str>) -> bool {
+    match version {
+        Some(version) => version_matches(version, range),
+        None => range.is_none(),
+    }
+}
+
+enum Node {
+    Installed { version: Option<String>, scope: InstallScope },
+    Download { version: String, source: String, data: Vec<u8>, dependencies: Vec<ThemeDependency> },
+}
+
+impl Node {
+    fn version(&self) -> Option<&str> {
+        match self {
+            Node::Installed { version, .. } => version.as_deref(),
+            Node::Download { version, .. } => Some(version),
+        }
+    }
+}
+
+// The plan plus the bundles it needs, already downloaded and in install order
+pub struct Resolution {
+    pub plan: DependencyPlan,
+    pub downloads: Vec<(String, Vec<u8>)>,
+}
+
+// Registry entries don't store versions, so read them from the installed reskin.json
+fn installed_theme(id: &str) -> Option<Node> {
+    [InstallScope::User, InstallScope::System].into_iter().find_map(|scope| {
+        let entry = find_installed(id, scope)?;
+        let version = installed_manifest(&entry).map(|manifest| manifest.version);
+        Some(Node::Installed { version, scope })
+    })
+}
+
+// Highest marketplace version of a theme that satisfies the range, returns (version, file id)
+fn pick_marketplace_theme(index: &Value, dependency: &ThemeDependency) -> Option<(String, String)> {
+    index["documents"].as_array()?
+        .iter()
+        .filter(|doc| doc["name"].as_str() == Some(dependency.id.as_str()))
+        .filter_map(|doc| Some((doc["version"].as_str()?.to_string(), doc["file"].as_str()?.to_string())))
+        .filter(|(version, _)| version_matches(version, dependency.version.as_deref()))
+        .max_by_key(|(version, _)| parse_version(version))
+}
+
+async fn fetch_dependency(dependency: &ThemeDependency, index: &mut Option<Value>, progress: &mut Progress) -> Result<(String, Vec<u8>), String> {
+    match dependency.source.as_deref() {
+        Some(url) if url.starts_with("https://") => Ok((url.to_string(), fetch_bundle_url(url, progress).await?)),
+        None | Some("marketplace") => {
+            if index.is_none() {
+                *index = Some(fetch_marketplace_themes("reskin", "themes").await?);
+            }
+            let (_, file_id) = pick_marketplace_theme(index.as_ref().unwrap(), dependency)
+                .ok_or("No matching version on the marketplace".to_string())?;
+            Ok(("marketplace".to_string(), fetch_theme_file(&file_id, progress).await?))
+        }
+        Some(source) => Err(format!("Unsupported source '{}'", source)),
+    }
+}
+
+fn missing(dependency: &ThemeDependency, required_by: &str, reason: String) -> MissingDependency {
+    MissingDependency {
+        id: dependency.id.clone(),
+        version: dependency.version.clone(),
+        required_by: required_by.to_string(),
+        reason,
+    }
+}
+
+// Walk the dependency tree, downloading what isn't installed, then order it so dependencies come first
+pub async fn resolve(manifest: &ThemeManifest, progress: &mut Progress) -> Result<Resolution, String> {
+    let mut nodes: HashMap<String, Node> = HashMap::new();
+    let mut required_by: HashMap<String, String> = HashMap::new();
+    let mut missing_deps = Vec::new();
+    let mut index = None;
+
+    let mut queue: VecDeque<(ThemeDependency, String)> = manifest.dependencies.iter()
+        .map(|dep| (dep.clone(), manifest.name.clone()))
+        .collect();
+
+    while let Some((dependency, parent)) = queue.pop_front() {
+        progress.check_cancelled()?;
+
+        // Already resolved through another theme, the versions have to agree
+        if let Some(node) = nodes.get(&dependency.id) {
+            if !satisfies(node.version(), dependency.version.as_deref()) {
+                let reason = format!("Conflicts with version {} required by {}",
+                    node.version().unwrap_or("unknown"), required_by[&dependency.id]);
+                missing_deps.push(missing(&dependency, &parent, reason));
+            }
+            continue;
+        }
+        // Depending on the theme being installed is a cycle, found when ordering
+        if dependency.id == manifest.name {
+            continue;
+        }
+
+        if let Some(installed) = installed_theme(&dependency.id) {
+            if satisfies(installed.version(), dependency.version.as_deref()) {
+                required_by.insert(dependency.id.clone(), parent);
+                nodes.insert(dependency.id.clone(), installed);
+                continue;
+            }
+        }
+
+        let (source, data) = match fetch_dependency(&dependency, &mut index, progress).await {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                missing_deps.push(missing(&dependency, &parent, e));
+                continue;
+            }
+        };
+        let dep_manifest = match extract_theme_info(data.clone()) {
+            Ok(dep_manifest) => dep_manifest,
+            Err(e) => {
+                missing_deps.push(missing(&dependency, &parent, e));
+                continue;
+            }
+        };
+        if dep_manifest.name != dependency.id {
+            let reason = format!("Source provides '{}' instead", dep_manifest.name);
+            missing_deps.push(missing(&dependency, &parent, reason));
+            continue;
+        }
+        if !version_matches(&dep_manifest.version, dependency.version.as_deref()) {
+            let reason = format!("Source provides version {}", dep_manifest.version);
+            missing_deps.push(missing(&dependency, &parent, reason));
+            continue;
+        }
+
+        for dep in &dep_manifest.dependencies {
+            queue.push_back((dep.clone(), dependency.id.clone()));
+        }
+        required_by.insert(dependency.id.clone(), parent);
+        nodes.insert(dependency.id.clone(), Node::Download {
+            version: dep_manifest.version,
+            source,
+            data,
+            dependencies: dep_manifest.dependencies,
+        });
+    }
+
+    let (order, cycles) = install_order(manifest, &nodes);
+    let mut plan = DependencyPlan { missing: missing_deps, cycles, ..Default::default() };
+
+    let mut downloads = Vec::new();
+    for id in order {
+        let node = match nodes.remove(&id) {
+            Some(node) => node,
+            None => continue, // Missing, already reported
+        };
+        let (action, source) = match &node {
+            Node::Installed { scope: InstallScope::User, .. } => ("installed", "user".to_string()),
+            Node::Installed { scope: InstallScope::System, .. } => ("installed", "system".to_string()),
+            Node::Download { source, .. } => ("install", source.clone()),
+        };
+        plan.steps.push(PlanStep {
+            id: id.clone(),
+            version: node.version().map(|v| v.to_string()),
+            action: action.to_string(),
+            source,
+            required_by: required_by.get(&id).cloned().unwrap_or_default(),
+        });
+        if let Node::Download { data, .. } = node {
+            downloads.push((id, data));
+        }
+    }
+
+    Ok(Resolution { plan, downloads })
+}
+
+// Dependencies before the themes that need them, plus every cycle found on the way
+fn install_order(manifest: &ThemeManifest, nodes: &HashMap<String, Node>) -> (Vec<String>, Vec<Vec<String>>) {
+    let mut order = Vec::new();
+    let mut cycles = Vec::new();
+    let mut done = HashSet::new();
+    let mut stack = vec![manifest.name.clone()];
+    for dependency in &manifest.dependencies {
+        visit(&dependency.id, nodes, &mut stack, &mut done, &mut order, &mut cycles);
+    }
+    (order, cycles)
+}
+
+// Depth-first, each theme is listed after everything it depends on
+fn visit(id: &str, nodes: &HashMap<String, Node>, stack: &mut Vec<String>, done: &mut HashSet<String>, order: &mut Vec<String>, cycles: &mut Vec<Vec<String>>) {
+    if let Some(pos) = stack.iter().position(|s| s == id) {
+        let mut cycle = stack[pos..].to_vec();
+        cycle.push(id.to_string());
+        cycles.push(cycle);
+        return;
+    }
+    if !done.insert(id.to_string()) {
+        return;
+    }
+
+    if let Some(Node::Download { dependencies, .. }) = nodes.get(id) {
+        stack.push(id.to_string());
+        for dependency in dependencies {
+            visit(&dependency.id, nodes, stack, done, order, cycles);
+        }
+        stack.pop();
+    }
+    order.push(id.to_string());
+}
+
+fn describe_problems(plan: &DependencyPlan) -> String {
+    let mut problems: Vec<String> = plan.missing.iter()
+        .map(|m| format!("{} {} (required by {}): {}", m.id, m.version.as_deref().unwrap_or("*"), m.required_by, m.reason))
+        .collect();
+    problems.extend(plan.cycles.iter().map(|cycle| format!("Dependency cycle: {}", cycle.join(" -> "))));
+    problems.join("\n")
+}
+
+// Installs run on blocking threads, so resolve on a thread of our own rather than nesting block_on in the runtime
+fn resolve_blocking(manifest: &ThemeManifest, progress: &mut Progress) -> Result<Resolution, String> {
+    std::thread::scope(|scope| {
+        scope.spawn(|| tauri::async_runtime::block_on(resolve(manifest, progress)))
+            .join()
+            .map_err(|_| "Dependency resolution failed".to_string())?
+    })
+}
+
+// Install whatever the manifest depends on that isn't there yet, returning the names installed
+pub fn install_dependencies(manifest: &ThemeManifest, scope: InstallScope, progress: &mut Progress) -> Result<Vec<String>, String> {
+    if manifest.dependencies.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    progress.phase("resolving dependencies", 0, 0);
+    let resolution = resolve_blocking(manifest, progress)?;
+    if !resolution.plan.missing.is_empty() || !resolution.plan.cycles.is_empty() {
+        return Err(format!("Can't install dependencies of '{}':\n{}", manifest.name, describe_problems(&resolution.plan)));
+    }
+
+    let mut installed = Vec::new();
+    for (id, data) in resolution.downloads {
+        let mark = progress.cleanup_mark();
+        let result = install_from_data(&data, &format!("{}.reskin", id), false, Some(scope), None, progress)
+            .map_err(|e| format!("Failed to install dependency '{}': {}", id, e))?;
+        if result.status != InstallStatus::Installed {
+            return Err(format!("Dependency '{}' conflicts with a theme that wasn't installed by Reskin", id));
+        }
+        // The dependency is registered now, a failure of the theme itself mustn't delete it
+        progress.keep_cleanup(mark);
+        installed.push(result.theme_name);
+    }
+    Ok(installed)
+}
+
+#[tauri::command]
+pub async fn resolve_dependencies(manifest: ThemeManifest) -> Result<DependencyPlan, String> {
+    let resolution = resolve(&manifest, &mut Progress::none()).await?;
+    Ok(resolution.plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_versions() {
+        let cases = [
+            ("1.2.3", Some([1, 2, 3])),
+            ("v1.2", Some([1, 2, 0])),
+            (" 2 ", Some([2, 0, 0])),
+            ("1.2.3-beta.1", Some([1, 2, 3])),
+            ("1.2.3+build", Some([1, 2, 3])),
+            ("1.2.3.4", Some([1, 2, 3])),
+            ("", None),
+            ("1.x", None),
+            ("latest", None),
+        ];
+        for (version, expected) in cases {
+            assert_eq!(parse_version(version), expected, "{}", version);
+        }
+    }
+
+    #[test]
+    fn matches_ranges() {
+        let cases = [
+            // No range, or one that allows anything
+            ("1.0.0", None, true),
+            ("1.0.0", Some(""), true),
+            ("1.0.0", Some("*"), true),
+            // Caret, also for bare versions
+            ("1.4.0", Some("^1.2"), true),
+            ("2.0.0", Some("^1.2"), false),
+            ("1.1.9", Some("^1.2"), false),
+            ("1.9.0", Some("1"), true),
+            ("0.2.5", Some("^0.2"), true),
+            ("0.3.0", Some("^0.2"), false),
+            ("0.0.3", Some("^0.0.3"), true),
+            ("0.0.4", Some("^0.0.3"), false),
+            ("0.0.9", Some("^0.0"), true),
+            ("0.1.0", Some("^0.0"), false),
+            ("0.9.0", Some("^0"), true),
+            // Tilde
+            ("1.2.9", Some("~1.2.3"), true),
+            ("1.3.0", Some("~1.2.3"), false),
+            ("1.9.0", Some("~1"), true),
+            ("2.0.0", Some("~1"), false),
+            // Comparisons, partial versions cover every release they name
+            ("1.2.0", Some(">=1.2"), true),
+            ("1.1.9", Some(">=1.2"), false),
+            ("1.2.5", Some(">1.2"), false),
+            ("1.3.0", Some(">1.2"), true),
+            ("1.2.1", Some(">1.2.0"), true),
+            ("1.9.9", Some("<2"), true),
+            ("2.0.0", Some("<2"), false),
+            ("1.2.7", Some("<=1.2"), true),
+            ("1.3.0", Some("<=1.2"), false),
+            ("1.2.7", Some("=1.2"), true),
+            ("1.2.7", Some("=1.2.3"), false),
+            ("1.2.3", Some("=1.2.3"), true),
+            // Lists, whitespace and prefixes
+            ("1.5.0", Some(">=1.0, <2"), true),
+            ("2.0.0", Some(">=1.0, <2"), false),
+            ("1.5.0", Some(" >= 1.0 ,< 2 "), true),
+            ("v1.2.3", Some("^1"), true),
+            ("1.2.3-rc.1", Some("=1.2.3"), true),
+            // Versions or requirements that don't parse never match
+            ("unknown", Some("^1"), false),
+            ("1.0.0", Some("^one"), false),
+            ("1.0.0", Some(">=1.0,"), true),
+        ];
+        for (version, range, expected) in cases {
+            assert_eq!(version_matches(version, range), expected, "{} {:?}", version, range);
+        }
+    }
+
+    #[test]
+    fn unversioned_installs_only_satisfy_open_ranges() {
+        assert!(satisfies(None, None));
+        assert!(!satisfies(None, Some("^1")));
+        assert!(satisfies(Some("1.2.0"), Some("^1")));
+    }
+
+    fn dependency(id: &str) -> ThemeDependency {
+        ThemeDependency { id: id.to_string(), version: None, source: None }
+    }
+
+    fn manifest(name: &str, dependencies: &[&str]) -> ThemeManifest {
+        let mut manifest: ThemeManifest = serde_json::from_str(&format!(
+            r#"{{"name":"{}","author":"","description":"","version":"1.0.0","tags":"","license":""}}"#, name
+        )).unwrap();
+        manifest.dependencies = dependencies.iter().map(|id| dependency(id)).collect();
+        manifest
+    }
+
+    fn download(dependencies: &[&str]) -> Node {
+        Node::Download {
+            version: "1.0.0".to_string(),
+            source: "marketplace".to_string(),
+            data: Vec::new(),
+            dependencies: dependencies.iter().map(|id| dependency(id)).collect(),
+        }
+    }
+
+    #[test]
+    fn orders_dependencies_first_and_once() {
+        // Both icons and cursors need Base, which is listed a single time before either
+        let nodes = HashMap::from([
+            ("Icons".to_string(), download(&["Base"])),
+            ("Cursors".to_string(), download(&["Base", "Icons"])),
+            ("Base".to_string(), Node::Installed { version: Some("2.0.0".to_string()), scope: InstallScope::User }),
+        ]);
+        let (order, cycles) = install_order(&manifest("Theme", &["Icons", "Cursors", "Icons"]), &nodes);
+        assert_eq!(order, ["Base", "Icons", "Cursors"]);
+        assert!(cycles.is_empty());
+    }
+
+    #[test]
+    fn reports_cycles() {
+        let nodes = HashMap::from([
+            ("A".to_string(), download(&["B"])),
+            ("B".to_string(), download(&["A"])),
+            ("C".to_string(), download(&["Theme"])),
+        ]);
+        let (order, cycles) = install_order(&manifest("Theme", &["A", "C"]), &nodes);
+        assert_eq!(cycles, [vec!["A", "B", "A"], vec!["Theme", "C", "Theme"]]);
+        assert_eq!(order, ["B", "A", "C"]);
+
+        // A theme depending on itself
+        let (_, cycles) = install_order(&manifest("Theme", &["Theme"]), &HashMap::new());
+        assert_eq!(cycles, [vec!["Theme", "Theme"]]);
+    }
+
+    #[test]
+    fn missing_dependencies_are_still_ordered() {
+        let (order, cycles) = install_order(&manifest("Theme", &["Gone"]), &HashMap::new());
+        assert_eq!(order, ["Gone"]);
+        assert!(cycles.is_empty());
+    }
+}
@@ -0,0 +1,60 @@
+// Budgie, which keeps GNOME's interface settings but has its own panel and window manager
+use std::path::Path;
+use crate::types::{AppearanceSnapshot, ColorScheme, ThemeFonts};
+use super::*;
+
+const PANEL: &str = "com.solus-project.budgie-panel";
+
+pub struct BudgieBackend;
+
+impl DesktopBackend for BudgieBackend {
+    fn name(&self) -> &'static str {
+        "Budgie"
+    }
+
+    fn detect(&self) -> bool {
+        desktop_is(&["BUDGIE", "BUDGIE-DESKTOP"])
+    }
+
+    fn apply_gtk(&self, theme: &str) -> ApplyResult {
+        GnomeBackend.apply_gtk(theme)
+    }
+
+    // budgie-wm draws decorations from the GTK theme and has no shell theme
+    fn apply_window_manager(&self, _theme: &str) -> ApplyResult {
+        Err(ApplyError::Unsupported)
+    }
+
+    fn apply_icons(&self, theme: &str) -> ApplyResult {
+        GnomeBackend.apply_icons(theme)
+    }
+
+    fn apply_cursor(&self, theme: &str, size: u32) -> ApplyResult {
+        GnomeBackend.apply_cursor(theme, size)
+    }
+
+    fn apply_fonts(&self, fonts: &ThemeFonts) -> ApplyResult {
+        GnomeBackend.apply_fonts(fonts)
+    }
+
+    fn apply_wallpaper(&self, path: &Path) -> ApplyResult {
+        GnomeBackend.apply_wallpaper(path)
+    }
+
+    // The panel keeps its own dark style switch next to the portal setting
+    fn apply_color_scheme(&self, scheme: ColorScheme) -> ApplyResult {
+        if has_schema(PANEL) {
+            gsettings_set(PANEL, "dark-theme", if scheme == ColorScheme::Dark { "true" } else { "false" })?;
+        }
+        Ok(set_portal_color_scheme(scheme)?)
+    }
+
+    fn snapshot(&self) -> AppearanceSnapshot {
+        let mut snapshot = GnomeBackend.snapshot();
+        snapshot.desktop = self.name().to_string();
+        for key in [WINDOW_MANAGER, SHELL, ACCENT_COLOR] {
+            snapshot.values.remove(key);
+        }
+        snapshot
+    }
+}
@@ -0,0 +1,109 @@
+// Cinnamon, configured through its org.cinnamon.* gsettings
+use std::path::Path;
+use crate::paths::find_theme_dir;
+use crate::types::{AppearanceSnapshot, ColorScheme, ThemeFonts};
+use super::*;
+
+const INTERFACE: &str = "org.cinnamon.desktop.interface";
+const WM_PREFERENCES: &str = "org.cinnamon.desktop.wm.preferences";
+const THEME: &str = "org.cinnamon.theme";
+// Cinnamon only has the interface font, the others stay in GNOME's schema
+const GNOME_INTERFACE: &str = "org.gnome.desktop.interface";
+// Cinnamon 6 hands its own color scheme to the XApp portal
+const XAPP_PORTAL: &str = "org.x.apps.portal";
+
+pub struct CinnamonBackend;
+
+impl DesktopBackend for CinnamonBackend {
+    fn name(&self) -> &'static str {
+        "Cinnamon"
+    }
+
+    fn detect(&self) -> bool {
+        desktop_is(&["X-CINNAMON", "CINNAMON"])
+    }
+
+    fn apply_gtk(&self, theme: &str) -> ApplyResult {
+        Ok(gsettings_set(INTERFACE, "gtk-theme", theme)?)
+    }
+
+    fn apply_window_manager(&self, theme: &str) -> ApplyResult {
+        Ok(gsettings_set(WM_PREFERENCES, "theme", theme)?)
+    }
+
+    // The panel and menus come from the theme's cinnamon/cinnamon.css
+    fn apply_shell(&self, theme: &str) -> ApplyResult {
+        let has_shell = find_theme_dir(theme).is_some_and(|dir| dir.join("cinnamon/cinnamon.css").is_file());
+        if !has_shell {
+            return Err(ApplyError::Failed(format!("{} has no cinnamon/ shell theme", theme)));
+        }
+        Ok(gsettings_set(THEME, "name", theme)?)
+    }
+
+    fn apply_icons(&self, theme: &str) -> ApplyResult {
+        Ok(gsettings_set(INTERFACE, "icon-theme", theme)?)
+    }
+
+    fn apply_cursor(&self, theme: &str, size: u32) -> ApplyResult {
+        gsettings_set(INTERFACE, "cursor-theme", theme)?;
+        Ok(gsettings_set(INTERFACE, "cursor-size", &size.to_string())?)
+    }
+
+    fn apply_fonts(&self, fonts: &ThemeFonts) -> ApplyResult {
+        let keys = [
+            (INTERFACE, "font-name", &fonts.interface),
+            (GNOME_INTERFACE, "document-font-name", &fonts.document),
+            (GNOME_INTERFACE, "monospace-font-name", &fonts.monospace),
+        ];
+        for (schema, key, font) in keys {
+            if let Some(font) = font {
+                let current = gsettings_get(schema, key);
+                gsettings_set(schema, key, &font_with_size(font, current.as_deref()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_wallpaper(&self, path: &Path) -> ApplyResult {
+        let uri = format!("file://{}", path.display());
+        Ok(gsettings_set("org.cinnamon.desktop.background", "picture-uri", &uri)?)
+    }
+
+    fn apply_color_scheme(&self, scheme: ColorScheme) -> ApplyResult {
+        if has_schema(XAPP_PORTAL) {
+            gsettings_set(XAPP_PORTAL, "color-scheme", match scheme {
+                ColorScheme::Default => "default",
+                ColorScheme::Light => "prefer-light",
+                ColorScheme::Dark => "prefer-dark",
+            })?;
+        }
+        Ok(set_portal_color_scheme(scheme)?)
+    }
+
+    fn snapshot(&self) -> AppearanceSnapshot {
+        let mut snapshot = AppearanceSnapshot { desktop: self.name().to_string(), ..Default::default() };
+        let entries = [
+            (GTK, INTERFACE, "gtk-theme"),
+            (WINDOW_MANAGER, WM_PREFERENCES, "theme"),
+            (SHELL, THEME, "name"),
+            (ICONS, INTERFACE, "icon-theme"),
+            (CURSOR, INTERFACE, "cursor-theme"),
+            (CURSOR_SIZE, INTERFACE, "cursor-size"),
+            (FONT_INTERFACE, INTERFACE, "font-name"),
+            (FONT_DOCUMENT, GNOME_INTERFACE, "document-font-name"),
+            (FONT_MONOSPACE, GNOME_INTERFACE, "monospace-font-name"),
+        ];
+        for (key, schema, gsettings_key) in entries {
+            if let Some(value) = gsettings_get(schema, gsettings_key) {
+                snapshot.values.insert(key.to_string(), value);
+            }
+        }
+        if let Some(uri) = gsettings_get("org.cinnamon.desktop.background", "picture-uri") {
+            snapshot.values.insert(WALLPAPER.to_string(), uri.trim_start_matches("file://").to_string());
+        }
+        if let Some(scheme) = get_portal_color_scheme() {
+            snapshot.values.insert(COLOR_SCHEME.to_string(), color_scheme_name(scheme).to_string());
+        }
+        snapshot
+    }
+}
@@ -0,0 +1,119 @@
+// GNOME and other desktops configured through org.gnome.desktop.* gsettings
+use std::path::Path;
+use crate::types::{AppearanceSnapshot, ColorScheme, ThemeFonts};
+use super::*;
+
+const INTERFACE: &str = "org.gnome.desktop.interface";
+const USER_THEME: &str = "org.gnome.shell.extensions.user-theme";
+const USER_THEME_EXTENSION: &str = "user-theme@gnome-shell-extensions.gcampax.github.com";
+
+pub struct GnomeBackend;
+
+// Enable the user-theme extension so its schema becomes available
+fn enable_user_theme_extension() -> Result<(), String> {
+    run("gnome-extensions", &["enable", USER_THEME_EXTENSION])?;
+    // Wait a moment for the extension to load
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    Ok(())
+}
+
+impl DesktopBackend for GnomeBackend {
+    fn name(&self) -> &'static str {
+        "GNOME"
+    }
+
+    fn detect(&self) -> bool {
+        desktop_is(&["GNOME", "GNOME-CLASSIC", "GNOME-FLASHBACK", "UNITY", "PANTHEON"])
+    }
+
+    fn apply_gtk(&self, theme: &str) -> ApplyResult {
+        Ok(gsettings_set(INTERFACE, "gtk-theme", theme)?)
+    }
+
+    // Mutter draws decorations from the GTK theme, this only covers legacy window managers
+    fn apply_window_manager(&self, theme: &str) -> ApplyResult {
+        Ok(gsettings_set("org.gnome.desktop.wm.preferences", "theme", theme)?)
+    }
+
+    fn apply_shell(&self, theme: &str) -> ApplyResult {
+        if !has_schema(USER_THEME) {
+            enable_user_theme_extension().map_err(|e| format!(
+                "user-theme extension not found. Install it with: `gnome-extensions install {}` or via GNOME Extensions app. Error: {}",
+                USER_THEME_EXTENSION, e
+            ))?;
+        }
+        Ok(gsettings_set(USER_THEME, "name", theme)?)
+    }
+
+    fn apply_icons(&self, theme: &str) -> ApplyResult {
+        Ok(gsettings_set(INTERFACE, "icon-theme", theme)?)
+    }
+
+    fn apply_cursor(&self, theme: &str, size: u32) -> ApplyResult {
+        gsettings_set(INTERFACE, "cursor-theme", theme)?;
+        Ok(gsettings_set(INTERFACE, "cursor-size", &size.to_string())?)
+    }
+
+    fn apply_fonts(&self, fonts: &ThemeFonts) -> ApplyResult {
+        let keys = [
+            ("font-name", &fonts.interface),
+            ("document-font-name", &fonts.document),
+            ("monospace-font-name", &fonts.monospace),
+        ];
+        for (key, font) in keys {
+            if let Some(font) = font {
+                let current = gsettings_get(INTERFACE, key);
+                gsettings_set(INTERFACE, key, &font_with_size(font, current.as_deref()))?;
+            }
+        }
+        Ok(())
+    }
+
+    // Dark style has its own key since GNOME 42
+    fn apply_wallpaper(&self, path: &Path) -> ApplyResult {
+        let uri = format!("file://{}", path.display());
+        gsettings_set("org.gnome.desktop.background", "picture-uri", &uri)?;
+        let _ = gsettings_set("org.gnome.desktop.background", "picture-uri-dark", &uri);
+        Ok(())
+    }
+
+    fn apply_color_scheme(&self, scheme: ColorScheme) -> ApplyResult {
+        Ok(set_portal_color_scheme(scheme)?)
+    }
+
+    // accent-color is new in GNOME 47, older versions reject the key
+    fn apply_accent_color(&self, color: &str) -> ApplyResult {
+        let rgb = parse_accent(color).ok_or(format!("Invalid accent color: {}", color))?;
+        Ok(gsettings_set(INTERFACE, "accent-color", nearest_gnome_accent(rgb))?)
+    }
+
+    fn snapshot(&self) -> AppearanceSnapshot {
+        let mut snapshot = AppearanceSnapshot { desktop: self.name().to_string(), ..Default::default() };
+        let entries = [
+            (GTK, INTERFACE, "gtk-theme"),
+            (WINDOW_MANAGER, "org.gnome.desktop.wm.preferences", "theme"),
+            (SHELL, USER_THEME, "name"),
+            (ICONS, INTERFACE, "icon-theme"),
+            (CURSOR, INTERFACE, "cursor-theme"),
+            (CURSOR_SIZE, INTERFACE, "cursor-size"),
+            (FONT_INTERFACE, INTERFACE, "font-name"),
+            (FONT_DOCUMENT, INTERFACE, "document-font-name"),
+            (FONT_MONOSPACE, INTERFACE, "monospace-font-name"),
+        ];
+        for (key, schema, gsettings_key) in entries {
+            if let Some(value) = gsettings_get(schema, gsettings_key) {
+                snapshot.values.insert(key.to_string(), value);
+            }
+        }
+        if let Some(uri) = gsettings_get("org.gnome.desktop.background", "picture-uri") {
+            snapshot.values.insert(WALLPAPER.to_string(), uri.trim_start_matches("file://").to_string());
+        }
+        if let Some(scheme) = get_portal_color_scheme() {
+            snapshot.values.insert(COLOR_SCHEME.to_string(), color_scheme_name(scheme).to_string());
+        }
+        if let Some(rgb) = gsettings_get(INTERFACE, "accent-color").as_deref().and_then(parse_accent) {
+            snapshot.values.insert(ACCENT_COLOR.to_string(), accent_hex(rgb));
+        }
+        snapshot
+    }
+}
@@ -0,0 +1,270 @@
+// KDE Plasma 5 and 6, configured through its rc files and plasma-apply-* tools
+use std::path::Path;
+use std::sync::OnceLock;
+use crate::paths::data_home;
+use crate::plasma::PlasmaAssetKind;
+use crate::types::{AppearanceSnapshot, ColorScheme, ThemeFonts};
+use super::*;
+
+const AURORAE_LIBRARY: &str = "org.kde.kwin.aurorae";
+const AURORAE_PREFIX: &str = "__aurorae__svg__";
+
+// Snapshot keys and the rc file entries they are read from
+const RC_ENTRIES: [(&str, &str, &str, &str); 9] = [
+    (WINDOW_MANAGER, "kwinrc", "org.kde.kdecoration2", "theme"),
+    (ICONS, "kdeglobals", "Icons", "Theme"),
+    (CURSOR, "kcminputrc", "Mouse", "cursorTheme"),
+    (CURSOR_SIZE, "kcminputrc", "Mouse", "cursorSize"),
+    (FONT_INTERFACE, "kdeglobals", "General", "font"),
+    (FONT_MONOSPACE, "kdeglobals", "General", "fixed"),
+    (PLASMA_COLORS, "kdeglobals", "General", "ColorScheme"),
+    (PLASMA_DESKTOP_THEME, "plasmarc", "Theme", "name"),
+    (PLASMA_LOOK_AND_FEEL, "kdeglobals", "KDE", "LookAndFeelPackage"),
+];
+
+pub struct KdeBackend;
+
+// Plasma 6 renamed the config tools, KDE_SESSION_VERSION is set by the session itself.
+// Looked up once, every config read and write needs it
+fn plasma_version() -> u32 {
+    static VERSION: OnceLock<u32> = OnceLock::new();
+    *VERSION.get_or_init(detect_plasma_version)
+}
+
+fn detect_plasma_version() -> u32 {
+    std::env::var("KDE_SESSION_VERSION").ok()
+        .and_then(|version| version.parse().ok())
+        .or_else(|| {
+            // "plasmashell 6.1.5"
+            read("plasmashell", &["--version"])?
+                .split_whitespace()
+                .last()?
+                .split('.')
+                .next()?
+                .parse()
+                .ok()
+        })
+        .unwrap_or(5)
+}
+
+fn kwriteconfig(file: &str, group: &str, key: &str, value: &str) -> Result<(), String> {
+    let tool = format!("kwriteconfig{}", plasma_version().max(5));
+    run(&tool, &["--file", file, "--group", group, "--key", key, value])
+}
+
+fn kreadconfig(file: &str, group: &str, key: &str) -> Option<String> {
+    let tool = format!("kreadconfig{}", plasma_version().max(5));
+    read(&tool, &["--file", file, "--group", group, "--key", key])
+}
+
+fn kdeleteconfig(file: &str, group: &str, key: &str) -> Result<(), String> {
+    let tool = format!("kwriteconfig{}", plasma_version().max(5));
+    run(&tool, &["--file", file, "--group", group, "--key", key, "--delete"])
+}
+
+// Tell running apps and KWin to reload, these are the signals the System Settings modules send
+fn notify_kwin() {
+    let _ = run("dbus-send", &["--session", "--type=signal", "/KWin", "org.kde.KWin.reloadConfig"]);
+}
+
+fn notify_icons() {
+    let _ = run("dbus-send", &["--session", "--type=signal", "/KIconLoader", "org.kde.KIconLoader.iconChanged", "int32:0"]);
+}
+
+fn has_aurorae_theme(name: &str) -> bool {
+    let user = data_home().map(|data| data.join("aurorae/themes").join(name));
+    user.into_iter()
+        .chain([Path::new("/usr/share/aurorae/themes").join(name)])
+        .any(|dir| dir.is_dir())
+}
+
+fn set_decoration(library: &str, theme: &str) -> Result<(), String> {
+    kwriteconfig("kwinrc", "org.kde.kdecoration2", "library", library)?;
+    kwriteconfig("kwinrc", "org.kde.kdecoration2", "theme", theme)?;
+    notify_kwin();
+    Ok(())
+}
+
+// Qt font strings are "Family,Size,-1,5,50,0,0,0,0,0"
+fn qt_font(font: &str, current: Option<&str>) -> String {
+    let font = font_with_size(font, current.map(font_from_qt).as_deref());
+    let (family, size) = font.rsplit_once(' ').unwrap_or((&font, "10"));
+    format!("{},{},-1,5,50,0,0,0,0,0", family, size)
+}
+
+fn font_from_qt(font: &str) -> String {
+    let mut parts = font.split(',');
+    match (parts.next(), parts.next()) {
+        (Some(family), Some(size)) => format!("{} {}", family, size),
+        _ => font.to_string(),
+    }
+}
+
+impl DesktopBackend for KdeBackend {
+    fn name(&self) -> &'static str {
+        "KDE"
+    }
+
+    fn detect(&self) -> bool {
+        desktop_is(&["KDE"])
+    }
+
+    // GTK apps under Plasma read the settings.ini files kde-gtk-config writes
+    fn apply_gtk(&self, theme: &str) -> ApplyResult {
+        Ok(set_gtk_setting("gtk-theme-name", theme)?)
+    }
+
+    // KWin can't draw GTK themes, only Aurorae decorations and its native Breeze
+    fn apply_window_manager(&self, theme: &str) -> ApplyResult {
+        if theme.starts_with(AURORAE_PREFIX) {
+            Ok(set_decoration(AURORAE_LIBRARY, theme)?)
+        } else if has_aurorae_theme(theme) {
+            Ok(set_decoration(AURORAE_LIBRARY, &format!("{}{}", AURORAE_PREFIX, theme))?)
+        } else if theme == "Breeze" {
+            Ok(set_decoration("org.kde.breeze", theme)?)
+        } else {
+            Err(ApplyError::Unsupported)
+        }
+    }
+
+    fn apply_icons(&self, theme: &str) -> ApplyResult {
+        kwriteconfig("kdeglobals", "Icons", "Theme", theme)?;
+        notify_icons();
+        let _ = set_gtk_setting("gtk-icon-theme-name", theme);
+        Ok(())
+    }
+
+    // plasma-apply-cursortheme also updates the running session, older Plasma only has the rc file
+    fn apply_cursor(&self, theme: &str, size: u32) -> ApplyResult {
+        let size = size.to_string();
+        if run("plasma-apply-cursortheme", &[theme, "--size", &size]).is_ok() {
+            return Ok(());
+        }
+        kwriteconfig("kcminputrc", "Mouse", "cursorTheme", theme)?;
+        Ok(kwriteconfig("kcminputrc", "Mouse", "cursorSize", &size)?)
+    }
+
+    // Plasma has no separate document font
+    fn apply_fonts(&self, fonts: &ThemeFonts) -> ApplyResult {
+        if fonts.interface.is_none() && fonts.monospace.is_none() {
+            return Err(ApplyError::Unsupported);
+        }
+        let keys = [("font", &fonts.interface), ("fixed", &fonts.monospace)];
+        for (key, font) in keys {
+            if let Some(font) = font {
+                let current = kreadconfig("kdeglobals", "General", key);
+                kwriteconfig("kdeglobals", "General", key, &qt_font(font, current.as_deref()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_wallpaper(&self, path: &Path) -> ApplyResult {
+        Ok(run("plasma-apply-wallpaperimage", &[&path.to_string_lossy()])?)
+    }
+
+    // Plasma's own colors only change through a .colors file the theme ships (applied with the
+    // KDE assets), swapping in Breeze would throw away the user's scheme. GTK apps get the hint
+    fn apply_color_scheme(&self, scheme: ColorScheme) -> ApplyResult {
+        let prefer_dark = match scheme {
+            ColorScheme::Dark => "1",
+            ColorScheme::Light => "0",
+            ColorScheme::Default => return Err(ApplyError::Unsupported),
+        };
+        Ok(set_gtk_setting("gtk-application-prefer-dark-theme", prefer_dark)?)
+    }
+
+    fn apply_accent_color(&self, color: &str) -> ApplyResult {
+        Ok(run("plasma-apply-colorscheme", &["--accent-color", color])?)
+    }
+
+    // The accent is baked into the scheme's colors, so the scheme goes on again without it
+    fn reset_accent_color(&self) -> ApplyResult {
+        kdeleteconfig("kdeglobals", "General", "AccentColor")?;
+        // Refused when the scheme is already current, the key is gone either way
+        if let Some(scheme) = kreadconfig("kdeglobals", "General", "ColorScheme") {
+            let _ = run("plasma-apply-colorscheme", &[&scheme]);
+        }
+        Ok(())
+    }
+
+    fn apply_plasma_asset(&self, kind: PlasmaAssetKind, name: &str) -> ApplyResult {
+        match kind {
+            PlasmaAssetKind::ColorScheme => Ok(run("plasma-apply-colorscheme", &[name])?),
+            PlasmaAssetKind::Aurorae => Ok(set_decoration(AURORAE_LIBRARY, &format!("{}{}", AURORAE_PREFIX, name))?),
+            PlasmaAssetKind::DesktopTheme => Ok(run("plasma-apply-desktoptheme", &[name])?),
+            // lookandfeeltool is the Plasma 5.0-5.20 name
+            PlasmaAssetKind::LookAndFeel => Ok(run("plasma-apply-lookandfeel", &["--apply", name])
+                .or_else(|_| run("lookandfeeltool", &["--apply", name]))?),
+        }
+    }
+
+    fn snapshot(&self) -> AppearanceSnapshot {
+        let mut snapshot = AppearanceSnapshot { desktop: self.name().to_string(), ..Default::default() };
+        // A fresh profile leaves most keys at Plasma's defaults, restore has to know to go back to them
+        for (key, file, group, rc_key) in RC_ENTRIES {
+            let value = match kreadconfig(file, group, rc_key) {
+                Some(font) if key == FONT_INTERFACE || key == FONT_MONOSPACE => font_from_qt(&font),
+                Some(value) => value,
+                None => UNSET.to_string(),
+            };
+            snapshot.values.insert(key.to_string(), value);
+        }
+        // Stored as "r,g,b", absent when the accent follows the color scheme
+        let accent = match kreadconfig("kdeglobals", "General", "AccentColor") {
+            Some(rgb) => {
+                let channels: Vec<u8> = rgb.split(',').filter_map(|c| c.trim().parse().ok()).collect();
+                (channels.len() == 3).then(|| accent_hex((channels[0], channels[1], channels[2])))
+            }
+            None => Some(UNSET.to_string()),
+        };
+        if let Some(accent) = accent {
+            snapshot.values.insert(ACCENT_COLOR.to_string(), accent);
+        }
+        if let Some(theme) = get_gtk_setting("gtk-theme-name") {
+            snapshot.values.insert(GTK.to_string(), theme);
+        }
+        snapshot
+    }
+
+    // Unset keys go back to Plasma's defaults first, the recorded values are applied over them
+    fn restore(&self, snapshot: &AppearanceSnapshot) -> Vec<ComponentReport> {
+        let mut values = snapshot.clone();
+        let mut reports = Vec::new();
+        for (key, file, group, rc_key) in RC_ENTRIES {
+            if snapshot.values.get(key).map(String::as_str) != Some(UNSET) {
+                continue;
+            }
+            values.values.remove(key);
+            let (label, result) = match key {
+                WINDOW_MANAGER => ("Window manager theme", self.apply_window_manager("Breeze")),
+                CURSOR => ("Cursor", self.apply_cursor("breeze_cursors", DEFAULT_CURSOR_SIZE)),
+                PLASMA_COLORS => {
+                    let breeze = if plasma_version() >= 6 { "BreezeLight" } else { "Breeze" };
+                    let kind = PlasmaAssetKind::ColorScheme;
+                    (asset_label(kind), self.apply_plasma_asset(kind, breeze))
+                }
+                PLASMA_DESKTOP_THEME => {
+                    let kind = PlasmaAssetKind::DesktopTheme;
+                    (asset_label(kind), self.apply_plasma_asset(kind, "default"))
+                }
+                // Applying a global theme would reset everything else, only forget the package
+                _ => {
+                    let label = match key {
+                        ICONS => "Icons",
+                        CURSOR_SIZE => "Cursor size",
+                        PLASMA_LOOK_AND_FEEL => asset_label(PlasmaAssetKind::LookAndFeel),
+                        _ => "Fonts",
+                    };
+                    (label, kdeleteconfig(file, group, rc_key).map_err(ApplyError::from))
+                }
+            };
+            if key == ICONS {
+                notify_icons();
+            }
+            reports.push(report(label, result));
+        }
+        reports.extend(restore_values(self, &values));
+        reports
+    }
+}
@@ -0,0 +1,88 @@
+// MATE, configured through org.mate.* gsettings with Marco as the window manager
+use std::path::Path;
+use crate::types::{AppearanceSnapshot, ColorScheme, ThemeFonts};
+use super::*;
+
+const INTERFACE: &str = "org.mate.interface";
+const MARCO: &str = "org.mate.Marco.general";
+const MOUSE: &str = "org.mate.peripherals-mouse";
+const BACKGROUND: &str = "org.mate.background";
+
+pub struct MateBackend;
+
+impl DesktopBackend for MateBackend {
+    fn name(&self) -> &'static str {
+        "MATE"
+    }
+
+    fn detect(&self) -> bool {
+        desktop_is(&["MATE"])
+    }
+
+    fn apply_gtk(&self, theme: &str) -> ApplyResult {
+        Ok(gsettings_set(INTERFACE, "gtk-theme", theme)?)
+    }
+
+    // Marco reads metacity-1 themes
+    fn apply_window_manager(&self, theme: &str) -> ApplyResult {
+        Ok(gsettings_set(MARCO, "theme", theme)?)
+    }
+
+    fn apply_icons(&self, theme: &str) -> ApplyResult {
+        Ok(gsettings_set(INTERFACE, "icon-theme", theme)?)
+    }
+
+    fn apply_cursor(&self, theme: &str, size: u32) -> ApplyResult {
+        gsettings_set(MOUSE, "cursor-theme", theme)?;
+        Ok(gsettings_set(MOUSE, "cursor-size", &size.to_string())?)
+    }
+
+    fn apply_fonts(&self, fonts: &ThemeFonts) -> ApplyResult {
+        let keys = [
+            ("font-name", &fonts.interface),
+            ("document-font-name", &fonts.document),
+            ("monospace-font-name", &fonts.monospace),
+        ];
+        for (key, font) in keys {
+            if let Some(font) = font {
+                let current = gsettings_get(INTERFACE, key);
+                gsettings_set(INTERFACE, key, &font_with_size(font, current.as_deref()))?;
+            }
+        }
+        Ok(())
+    }
+
+    // MATE stores a plain path rather than a URI
+    fn apply_wallpaper(&self, path: &Path) -> ApplyResult {
+        Ok(gsettings_set(BACKGROUND, "picture-filename", &path.to_string_lossy())?)
+    }
+
+    // MATE has no color scheme of its own, the GTK portal hands this one to apps
+    fn apply_color_scheme(&self, scheme: ColorScheme) -> ApplyResult {
+        Ok(set_portal_color_scheme(scheme)?)
+    }
+
+    fn snapshot(&self) -> AppearanceSnapshot {
+        let mut snapshot = AppearanceSnapshot { desktop: self.name().to_string(), ..Default::default() };
+        let entries = [
+            (GTK, INTERFACE, "gtk-theme"),
+            (WINDOW_MANAGER, MARCO, "theme"),
+            (ICONS, INTERFACE, "icon-theme"),
+            (CURSOR, MOUSE, "cursor-theme"),
+            (CURSOR_SIZE, MOUSE, "cursor-size"),
+            (FONT_INTERFACE, INTERFACE, "font-name"),
+            (FONT_DOCUMENT, INTERFACE, "document-font-name"),
+            (FONT_MONOSPACE, INTERFACE, "monospace-font-name"),
+            (WALLPAPER, BACKGROUND, "picture-filename"),
+        ];
+        for (key, schema, gsettings_key) in entries {
+            if let Some(value) = gsettings_get(schema, gsettings_key) {
+                snapshot.values.insert(key.to_string(), value);
+            }
+        }
+        if let Some(scheme) = get_portal_color_scheme() {
+            snapshot.values.insert(COLOR_SCHEME.to_string(), color_scheme_name(scheme).to_string());
+        }
+        snapshot
+    }
+}
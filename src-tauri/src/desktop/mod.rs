@@ -0,0 +1,513 @@
+// Desktop backends: how each desktop environment applies theme components
+mod budgie;
+mod cinnamon;
+mod gnome;
+mod kde;
+mod mate;
+mod wlroots;
+mod xfce;
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use crate::paths::config_home;
+use crate::plasma::{asset_label, PlasmaAssetKind};
+use crate::types::{AppearanceSnapshot, ColorScheme, ComponentReport, ComponentStatus, ThemeFonts};
+
+pub use budgie::BudgieBackend;
+pub use cinnamon::CinnamonBackend;
+pub use gnome::GnomeBackend;
+pub use kde::KdeBackend;
+pub use mate::MateBackend;
+pub use wlroots::WlrootsBackend;
+pub use xfce::XfceBackend;
+
+pub enum ApplyError {
+    Unsupported,
+    Failed(String),
+}
+
+impl From<String> for ApplyError {
+    fn from(e: String) -> ApplyError {
+        ApplyError::Failed(e)
+    }
+}
+
+pub type ApplyResult = Result<(), ApplyError>;
+
+// Snapshot keys, shared by every backend so restore can dispatch on them
+pub const GTK: &str = "gtk";
+pub const WINDOW_MANAGER: &str = "window_manager";
+pub const SHELL: &str = "shell";
+pub const ICONS: &str = "icons";
+pub const CURSOR: &str = "cursor";
+pub const CURSOR_SIZE: &str = "cursor_size";
+pub const FONT_INTERFACE: &str = "font_interface";
+pub const FONT_DOCUMENT: &str = "font_document";
+pub const FONT_MONOSPACE: &str = "font_monospace";
+pub const WALLPAPER: &str = "wallpaper";
+pub const COLOR_SCHEME: &str = "color_scheme";
+pub const ACCENT_COLOR: &str = "accent_color";
+pub const PLASMA_COLORS: &str = "plasma_colors";
+pub const PLASMA_DESKTOP_THEME: &str = "plasma_desktop_theme";
+pub const PLASMA_LOOK_AND_FEEL: &str = "plasma_look_and_feel";
+
+// Recorded for a setting that wasn't set, restoring it unsets the setting again
+pub const UNSET: &str = "";
+
+pub const DEFAULT_CURSOR_SIZE: u32 = 24;
+
+// GNOME 47 accent names and the colors libadwaita uses for them
+pub const GNOME_ACCENTS: [(&str, (u8, u8, u8)); 9] = [
+    ("blue", (0x35, 0x84, 0xe4)),
+    ("teal", (0x21, 0x90, 0xa4)),
+    ("green", (0x3a, 0x94, 0x4a)),
+    ("yellow", (0xc8, 0x88, 0x00)),
+    ("orange", (0xed, 0x5b, 0x00)),
+    ("red", (0xe6, 0x2d, 0x42)),
+    ("pink", (0xd5, 0x61, 0x99)),
+    ("purple", (0x91, 0x41, 0xac)),
+    ("slate", (0x6f, 0x83, 0x96)),
+];
+
+// Components a desktop doesn't have are left at Unsupported
+pub trait DesktopBackend {
+    fn name(&self) -> &'static str;
+
+    // Whether this is the desktop the session is running
+    fn detect(&self) -> bool;
+
+    fn apply_gtk(&self, _theme: &str) -> ApplyResult {
+        Err(ApplyError::Unsupported)
+    }
+
+    fn apply_window_manager(&self, _theme: &str) -> ApplyResult {
+        Err(ApplyError::Unsupported)
+    }
+
+    fn apply_shell(&self, _theme: &str) -> ApplyResult {
+        Err(ApplyError::Unsupported)
+    }
+
+    fn apply_icons(&self, _theme: &str) -> ApplyResult {
+        Err(ApplyError::Unsupported)
+    }
+
+    fn apply_cursor(&self, _theme: &str, _size: u32) -> ApplyResult {
+        Err(ApplyError::Unsupported)
+    }
+
+    // Fonts are "Family Size" strings, fields left None keep their current value
+    fn apply_fonts(&self, _fonts: &ThemeFonts) -> ApplyResult {
+        Err(ApplyError::Unsupported)
+    }
+
+    fn apply_wallpaper(&self, _path: &Path) -> ApplyResult {
+        Err(ApplyError::Unsupported)
+    }
+
+    fn apply_color_scheme(&self, _scheme: ColorScheme) -> ApplyResult {
+        Err(ApplyError::Unsupported)
+    }
+
+    // Accents always arrive as "#rrggbb"
+    fn apply_accent_color(&self, _color: &str) -> ApplyResult {
+        Err(ApplyError::Unsupported)
+    }
+
+    // Go back to the accent the color scheme brings
+    fn reset_accent_color(&self) -> ApplyResult {
+        Err(ApplyError::Unsupported)
+    }
+
+    // Color schemes, decorations and global themes shipped for Plasma
+    fn apply_plasma_asset(&self, _kind: PlasmaAssetKind, _name: &str) -> ApplyResult {
+        Err(ApplyError::Unsupported)
+    }
+
+    // Current values of everything the backend can apply
+    fn snapshot(&self) -> AppearanceSnapshot;
+
+    // Put back a snapshot by applying each recorded value again
+    fn restore(&self, snapshot: &AppearanceSnapshot) -> Vec<ComponentReport> {
+        restore_values(self, snapshot)
+    }
+}
+
+// The default restore, for backends that put back more than the shared keys
+pub fn restore_values<B: DesktopBackend + ?Sized>(backend: &B, snapshot: &AppearanceSnapshot) -> Vec<ComponentReport> {
+    let value = |key: &str| snapshot.values.get(key).map(|v| v.as_str());
+    let mut reports = Vec::new();
+
+    // A global theme resets the other settings, so it goes back first
+    let plasma = [
+        (PLASMA_LOOK_AND_FEEL, PlasmaAssetKind::LookAndFeel),
+        (PLASMA_DESKTOP_THEME, PlasmaAssetKind::DesktopTheme),
+        (PLASMA_COLORS, PlasmaAssetKind::ColorScheme),
+    ];
+    for (key, kind) in plasma {
+        if let Some(name) = value(key) {
+            reports.push(report(asset_label(kind), backend.apply_plasma_asset(kind, name)));
+        }
+    }
+    if let Some(theme) = value(GTK) {
+        reports.push(report("GTK theme", backend.apply_gtk(theme)));
+    }
+    if let Some(theme) = value(WINDOW_MANAGER) {
+        reports.push(report("Window manager theme", backend.apply_window_manager(theme)));
+    }
+    if let Some(theme) = value(SHELL) {
+        reports.push(report("Shell theme", backend.apply_shell(theme)));
+    }
+    if let Some(theme) = value(ICONS) {
+        reports.push(report("Icons", backend.apply_icons(theme)));
+    }
+    if let Some(theme) = value(CURSOR) {
+        let size = value(CURSOR_SIZE).and_then(|s| s.parse().ok()).unwrap_or(DEFAULT_CURSOR_SIZE);
+        reports.push(report("Cursor", backend.apply_cursor(theme, size)));
+    }
+    let fonts = ThemeFonts {
+        interface: value(FONT_INTERFACE).map(|f| f.to_string()),
+        document: value(FONT_DOCUMENT).map(|f| f.to_string()),
+        monospace: value(FONT_MONOSPACE).map(|f| f.to_string()),
+    };
+    if fonts.interface.is_some() || fonts.document.is_some() || fonts.monospace.is_some() {
+        reports.push(report("Fonts", backend.apply_fonts(&fonts)));
+    }
+    if let Some(path) = value(WALLPAPER) {
+        reports.push(report("Wallpaper", backend.apply_wallpaper(Path::new(path))));
+    }
+    if let Some(scheme) = value(COLOR_SCHEME).and_then(parse_color_scheme) {
+        reports.push(report("Color scheme", backend.apply_color_scheme(scheme)));
+    }
+    match value(ACCENT_COLOR) {
+        Some(UNSET) => reports.push(report("Accent color", backend.reset_accent_color())),
+        Some(color) => reports.push(report("Accent color", backend.apply_accent_color(color))),
+        None => {}
+    }
+
+    reports
+}
+
+fn backends() -> Vec<Box<dyn DesktopBackend>> {
+    vec![
+        Box::new(XfceBackend),
+        Box::new(KdeBackend),
+        Box::new(CinnamonBackend),
+        Box::new(MateBackend),
+        Box::new(BudgieBackend),
+        Box::new(WlrootsBackend),
+        Box::new(GnomeBackend),
+    ]
+}
+
+// The running desktop's backend, gsettings is the most widely understood when nothing matches
+pub fn detect_backend() -> Box<dyn DesktopBackend> {
+    backends().into_iter()
+        .find(|backend| backend.detect())
+        .unwrap_or_else(|| Box::new(GnomeBackend))
+}
+
+// XDG_CURRENT_DESKTOP is a colon-separated list such as "ubuntu:GNOME"
+pub fn desktop_is(names: &[&str]) -> bool {
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default().to_uppercase();
+    desktop.split(':').any(|entry| names.contains(&entry))
+}
+
+pub fn report(component: &str, result: ApplyResult) -> ComponentReport {
+    let (status, message) = match result {
+        Ok(()) => (ComponentStatus::Applied, None),
+        Err(ApplyError::Unsupported) => (ComponentStatus::Unsupported, None),
+        Err(ApplyError::Failed(e)) => (ComponentStatus::Failed, Some(e)),
+    };
+    ComponentReport { component: component.to_string(), status, message }
+}
+
+// The user-facing message for a set of reports, an error when nothing applied
+pub fn summarize(reports: &[ComponentReport], failure: &str) -> Result<String, String> {
+    let names = |status: ComponentStatus| reports.iter()
+        .filter(|r| r.status == status)
+        .map(|r| r.component.as_str())
+        .collect::<Vec<_>>();
+    let applied = names(ComponentStatus::Applied);
+    let unsupported = names(ComponentStatus::Unsupported);
+    let skipped = names(ComponentStatus::Skipped);
+    let warnings: Vec<String> = reports.iter()
+        .filter(|r| r.status == ComponentStatus::Failed)
+        .map(|r| format!("{} failed: {}", r.component, r.message.as_deref().unwrap_or("unknown error")))
+        .collect();
+
+    let mut message = String::new();
+    if !applied.is_empty() {
+        message.push_str(&format!("✅ Applied: {}\n", applied.join(", ")));
+    }
+    if !warnings.is_empty() {
+        message.push_str(&format!("⚠️  Warnings:\n{}\n", warnings.join("\n")));
+    }
+    if !unsupported.is_empty() {
+        message.push_str(&format!("Not supported on this desktop: {}\n", unsupported.join(", ")));
+    }
+    if !skipped.is_empty() {
+        message.push_str(&format!("Not included in this theme: {}", skipped.join(", ")));
+    }
+
+    if applied.is_empty() && warnings.is_empty() {
+        Err(failure.to_string())
+    } else if applied.is_empty() {
+        Err(format!("{}:\n{}", failure, warnings.join("\n")))
+    } else {
+        Ok(message.trim_end().to_string())
+    }
+}
+
+pub fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+// Trimmed stdout of a successful command
+pub fn read(program: &str, args: &[&str]) -> Option<String> {
+    Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+// "Inter" becomes "Inter 11", taking the size from the font currently set when there is one
+pub fn font_with_size(font: &str, current: Option<&str>) -> String {
+    let has_size = |f: &str| f.rsplit_once(' ').is_some_and(|(_, size)| size.parse::<f32>().is_ok());
+    if has_size(font) {
+        return font.to_string();
+    }
+    let size = current
+        .filter(|c| has_size(c))
+        .and_then(|c| c.rsplit_once(' '))
+        .map(|(_, size)| size)
+        .unwrap_or("11");
+    format!("{} {}", font.trim(), size)
+}
+
+pub fn color_scheme_name(scheme: ColorScheme) -> &'static str {
+    match scheme {
+        ColorScheme::Default => "default",
+        ColorScheme::Light => "light",
+        ColorScheme::Dark => "dark",
+    }
+}
+
+pub fn parse_color_scheme(name: &str) -> Option<ColorScheme> {
+    match name {
+        "default" => Some(ColorScheme::Default),
+        "light" => Some(ColorScheme::Light),
+        "dark" => Some(ColorScheme::Dark),
+        _ => None,
+    }
+}
+
+// "#3584e4", "3584e4" or a GNOME accent name
+pub fn parse_accent(color: &str) -> Option<(u8, u8, u8)> {
+    let color = color.trim().to_lowercase();
+    if let Some((_, rgb)) = GNOME_ACCENTS.iter().find(|(name, _)| *name == color) {
+        return Some(*rgb);
+    }
+    let hex = color.trim_start_matches('#');
+    // Byte slicing below needs ASCII, and from_str_radix would also take a sign
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+pub fn accent_hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// GNOME only offers its fixed palette, so pick the closest entry
+pub fn nearest_gnome_accent((r, g, b): (u8, u8, u8)) -> &'static str {
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(r, r2) + d(g, g2) + d(b, b2)
+    };
+    GNOME_ACCENTS.iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map(|(name, _)| *name)
+        .unwrap_or("blue")
+}
+
+// xdg-desktop-portal-gnome and -gtk both expose this key as org.freedesktop.appearance color-scheme
+pub fn set_portal_color_scheme(scheme: ColorScheme) -> Result<(), String> {
+    let set = |value: &str| run("gsettings", &["set", "org.gnome.desktop.interface", "color-scheme", value]);
+    match scheme {
+        ColorScheme::Default => set("default"),
+        ColorScheme::Dark => set("prefer-dark"),
+        // prefer-light only exists since GNOME 47
+        ColorScheme::Light => set("prefer-light").or_else(|_| set("default")),
+    }
+}
+
+pub fn get_portal_color_scheme() -> Option<ColorScheme> {
+    match read("gsettings", &["get", "org.gnome.desktop.interface", "color-scheme"])?.trim_matches('\'') {
+        "prefer-dark" => Some(ColorScheme::Dark),
+        "prefer-light" => Some(ColorScheme::Light),
+        _ => Some(ColorScheme::Default),
+    }
+}
+
+pub fn gsettings_set(schema: &str, key: &str, value: &str) -> Result<(), String> {
+    run("gsettings", &["set", schema, key, value])
+}
+
+// gsettings prints strings quoted, 'Adwaita'
+pub fn gsettings_get(schema: &str, key: &str) -> Option<String> {
+    read("gsettings", &["get", schema, key]).map(|value| value.trim_matches('\'').to_string())
+}
+
+pub fn has_schema(schema: &str) -> bool {
+    read("gsettings", &["list-schemas"]).is_some_and(|schemas| schemas.lines().any(|line| line == schema))
+}
+
+// Set a key in ~/.config/gtk-3.0/settings.ini and gtk-4.0/settings.ini, for desktops without a settings daemon
+pub fn set_gtk_setting(key: &str, value: &str) -> Result<(), String> {
+    for version in ["gtk-3.0", "gtk-4.0"] {
+        let dir = config_home()?.join(version);
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let path = dir.join("settings.ini");
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+
+        fs::write(&path, with_gtk_setting(&existing, key, value))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+// settings.ini with the key moved to the top of [Settings], creating the group when it's missing
+fn with_gtk_setting(existing: &str, key: &str, value: &str) -> String {
+    let mut lines: Vec<String> = existing.lines()
+        .filter(|line| line.split('=').next().map(str::trim) != Some(key))
+        .map(|line| line.to_string())
+        .collect();
+    let entry = format!("{}={}", key, value);
+    match lines.iter().position(|line| line.trim() == "[Settings]") {
+        Some(pos) => lines.insert(pos + 1, entry),
+        None => {
+            lines.insert(0, entry);
+            lines.insert(0, "[Settings]".to_string());
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+pub fn get_gtk_setting(key: &str) -> Option<String> {
+    let content = fs::read_to_string(config_home().ok()?.join("gtk-3.0/settings.ini")).ok()?;
+    content.lines()
+        .filter_map(|line| line.split_once('='))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, v)| v.trim().to_string())
+}
+
+#[tauri::command]
+pub fn detect_desktop() -> Result<String, String> {
+    Ok(detect_backend().name().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_or_borrows_font_sizes() {
+        assert_eq!(font_with_size("Inter 12", Some("Cantarell 11")), "Inter 12");
+        assert_eq!(font_with_size("Inter", Some("Cantarell 10.5")), "Inter 10.5");
+        assert_eq!(font_with_size("Inter", Some("Cantarell Bold")), "Inter 11");
+        assert_eq!(font_with_size("Noto Sans", None), "Noto Sans 11");
+        assert_eq!(font_with_size(" Inter ", Some("Cantarell 9")), "Inter 9");
+        assert_eq!(font_with_size("Source Code Pro Semi-Bold 10", None), "Source Code Pro Semi-Bold 10");
+    }
+
+    #[test]
+    fn summarizes_reports() {
+        let reports = [
+            report("GTK theme", Ok(())),
+            report("Icons", Ok(())),
+            report("Shell theme", Err(ApplyError::Unsupported)),
+            report("Cursor", Err(ApplyError::Failed("no such theme".to_string()))),
+            report("Fonts", Err(ApplyError::Failed(String::new()))),
+            ComponentReport { component: "Kvantum theme".to_string(), status: ComponentStatus::Skipped, message: None },
+        ];
+        assert_eq!(
+            summarize(&reports, "Failed").unwrap(),
+            "✅ Applied: GTK theme, Icons\n⚠️  Warnings:\nCursor failed: no such theme\nFonts failed: \nNot supported on this desktop: Shell theme\nNot included in this theme: Kvantum theme"
+        );
+
+        let failed = [report("Icons", Err(ApplyError::Failed("gsettings missing".to_string())))];
+        assert_eq!(summarize(&failed, "Failed to apply").unwrap_err(), "Failed to apply:\nIcons failed: gsettings missing");
+
+        let unsupported = [report("Shell theme", Err(ApplyError::Unsupported))];
+        assert_eq!(summarize(&unsupported, "Failed to apply").unwrap_err(), "Failed to apply");
+        assert_eq!(summarize(&[], "Nothing").unwrap_err(), "Nothing");
+
+        assert_eq!(summarize(&[report("GTK theme", Ok(()))], "Failed").unwrap(), "✅ Applied: GTK theme");
+    }
+
+    #[test]
+    fn parses_accents() {
+        assert_eq!(parse_accent("#3584e4"), Some((0x35, 0x84, 0xe4)));
+        assert_eq!(parse_accent(" 3584E4 "), Some((0x35, 0x84, 0xe4)));
+        assert_eq!(parse_accent("Teal"), Some((0x21, 0x90, 0xa4)));
+        assert_eq!(parse_accent("#3584e"), None);
+        assert_eq!(parse_accent("#3584e4ff"), None);
+        assert_eq!(parse_accent("#zz84e4"), None);
+        assert_eq!(parse_accent("magenta"), None);
+        assert_eq!(parse_accent("#ééé"), None);
+        assert_eq!(parse_accent("#aééb"), None);
+        assert_eq!(parse_accent("#+f+f+f"), None);
+        assert_eq!(accent_hex((0x35, 0x84, 0xe4)), "#3584e4");
+    }
+
+    #[test]
+    fn picks_nearest_gnome_accent() {
+        for (name, rgb) in GNOME_ACCENTS {
+            assert_eq!(nearest_gnome_accent(rgb), name);
+        }
+        assert_eq!(nearest_gnome_accent((0x88, 0xc0, 0xd0)), "slate");
+        assert_eq!(nearest_gnome_accent((0xff, 0x00, 0x00)), "red");
+        assert_eq!(nearest_gnome_accent((0x00, 0x80, 0x80)), "teal");
+        assert_eq!(nearest_gnome_accent((0xff, 0xa5, 0x00)), "yellow");
+    }
+
+    #[test]
+    fn gtk_settings_lines() {
+        assert_eq!(with_gtk_setting("", "gtk-theme-name", "Nordic"), "[Settings]\ngtk-theme-name=Nordic\n");
+
+        let existing = "# my settings\n[Settings]\ngtk-font-name=Inter 11\ngtk-theme-name = Adwaita\ngtk-icon-theme-name=Papirus\n";
+        assert_eq!(
+            with_gtk_setting(existing, "gtk-theme-name", "Nordic"),
+            "# my settings\n[Settings]\ngtk-theme-name=Nordic\ngtk-font-name=Inter 11\ngtk-icon-theme-name=Papirus\n"
+        );
+
+        // Keys that merely start with the same name are left alone
+        let existing = "[Settings]\ngtk-theme-name-extra=1\n";
+        assert_eq!(
+            with_gtk_setting(existing, "gtk-theme-name", "Nordic"),
+            "[Settings]\ngtk-theme-name=Nordic\ngtk-theme-name-extra=1\n"
+        );
+
+        // Files without the group get one in front of whatever was there
+        assert_eq!(
+            with_gtk_setting("gtk-theme-name=Old\nfoo=bar", "gtk-theme-name", "Nordic"),
+            "[Settings]\ngtk-theme-name=Nordic\nfoo=bar\n"
+        );
+    }
+}
@@ -0,0 +1,320 @@
+// Hyprland, Sway and other wlroots compositors: no settings daemon, so GTK reads settings.ini
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::cursors::write_cursor_environment;
+use crate::palette::read_palette;
+use crate::paths::{config_home, find_theme_dir};
+use crate::types::{AppearanceSnapshot, ColorScheme, ThemeFonts};
+use super::*;
+
+// Include file reskin owns next to the compositor config, rewritten on every apply
+const INCLUDE_FILE: &str = "reskin.conf";
+const INCLUDE_HEADER: &str = "# Managed by reskin, changes are overwritten on the next apply";
+// Each part of the include file starts with "# reskin:<block> <value>"
+const BLOCK_PREFIX: &str = "# reskin:";
+// Snapshot key for the whole include file, so undo also drops blocks the snapshot didn't have
+const INCLUDE_BLOCKS: &str = "compositor_blocks";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Compositor {
+    Hyprland,
+    Sway,
+    Other,
+}
+
+pub struct WlrootsBackend;
+
+fn compositor() -> Compositor {
+    if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() || desktop_is(&["HYPRLAND"]) {
+        Compositor::Hyprland
+    } else if std::env::var("SWAYSOCK").is_ok() || desktop_is(&["SWAY"]) {
+        Compositor::Sway
+    } else {
+        Compositor::Other
+    }
+}
+
+// The compositor's main config and the line that pulls our include file in
+fn compositor_config(compositor: Compositor) -> Result<(PathBuf, PathBuf, String), String> {
+    let config = config_home()?;
+    match compositor {
+        Compositor::Hyprland => {
+            let include = config.join("hypr").join(INCLUDE_FILE);
+            let line = format!("source = {}", include.display());
+            Ok((config.join("hypr/hyprland.conf"), include, line))
+        }
+        Compositor::Sway => {
+            let include = config.join("sway").join(INCLUDE_FILE);
+            let line = format!("include {}", include.display());
+            Ok((config.join("sway/config"), include, line))
+        }
+        Compositor::Other => Err("No supported compositor config".to_string()),
+    }
+}
+
+// Blocks of the include file by name, with the value recorded in their header
+fn read_blocks(include: &Path) -> Vec<(String, String, Vec<String>)> {
+    let content = fs::read_to_string(include).unwrap_or_default();
+    let mut blocks: Vec<(String, String, Vec<String>)> = Vec::new();
+    for line in content.lines() {
+        if let Some(header) = line.strip_prefix(BLOCK_PREFIX) {
+            let (name, value) = header.split_once(' ').unwrap_or((header, ""));
+            blocks.push((name.to_string(), value.to_string(), Vec::new()));
+        } else if let Some((_, _, lines)) = blocks.last_mut() {
+            if !line.trim().is_empty() {
+                lines.push(line.to_string());
+            }
+        }
+    }
+    blocks
+}
+
+// The value a block was written for, such as the theme the border colors came from
+fn block_value(name: &str) -> Option<String> {
+    let (_, include, _) = compositor_config(compositor()).ok()?;
+    read_blocks(&include).into_iter()
+        .find(|(block, _, _)| block == name)
+        .map(|(_, value, _)| value)
+}
+
+// Replace one block of the include file and make sure the main config sources it
+fn write_block(name: &str, value: &str, lines: Vec<String>) -> Result<(), String> {
+    let compositor = compositor();
+    let (config, include, include_line) = compositor_config(compositor)?;
+    if !config.is_file() {
+        return Err(format!("{} not found, the compositor is using its default config", config.display()));
+    }
+
+    let mut blocks = read_blocks(&include);
+    match blocks.iter_mut().find(|(block, _, _)| block == name) {
+        Some(block) => *block = (name.to_string(), value.to_string(), lines),
+        None => blocks.push((name.to_string(), value.to_string(), lines)),
+    }
+    let mut content = format!("{}\n", INCLUDE_HEADER);
+    for (block, value, lines) in &blocks {
+        content.push_str(&format!("\n{}{} {}\n", BLOCK_PREFIX, block, value));
+        for line in lines {
+            content.push_str(&format!("{}\n", line));
+        }
+    }
+    fs::write(&include, content)
+        .map_err(|e| format!("Failed to write {}: {}", include.display(), e))?;
+
+    let existing = fs::read_to_string(&config)
+        .map_err(|e| format!("Failed to read {}: {}", config.display(), e))?;
+    if !existing.lines().any(|line| line.trim() == include_line) {
+        let separator = if existing.ends_with('\n') || existing.is_empty() { "" } else { "\n" };
+        fs::write(&config, format!("{}{}\n# Added by reskin\n{}\n", existing, separator, include_line))
+            .map_err(|e| format!("Failed to update {}: {}", config.display(), e))?;
+    }
+
+    reload(compositor);
+    Ok(())
+}
+
+// Put the include file back as a snapshot recorded it, an unset one keeps only the header
+fn restore_include(content: &str) -> Result<(), String> {
+    let compositor = compositor();
+    let (_, include, _) = compositor_config(compositor)?;
+    if content == UNSET && !include.is_file() {
+        return Ok(());
+    }
+    let content = if content == UNSET { format!("{}\n", INCLUDE_HEADER) } else { content.to_string() };
+    fs::write(&include, content)
+        .map_err(|e| format!("Failed to write {}: {}", include.display(), e))?;
+    reload(compositor);
+    Ok(())
+}
+
+// Only reload a compositor that is actually running
+fn reload(compositor: Compositor) {
+    match compositor {
+        Compositor::Hyprland if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() => {
+            let _ = run("hyprctl", &["reload"]);
+        }
+        Compositor::Sway if std::env::var("SWAYSOCK").is_ok() => {
+            let _ = run("swaymsg", &["reload"]);
+        }
+        _ => {}
+    }
+}
+
+// gsettings only matters to apps that go through the portal, and may have no dconf to write to
+fn try_gsettings(key: &str, value: &str) {
+    let _ = run("gsettings", &["set", "org.gnome.desktop.interface", key, value]);
+}
+
+// Hyprland wants rgb(rrggbb), sway #rrggbb
+fn border_lines(compositor: Compositor, theme: &str) -> Result<Vec<String>, String> {
+    let theme_dir = find_theme_dir(theme)
+        .ok_or_else(|| format!("{} is not installed", theme))?;
+    let palette = read_palette(&theme_dir);
+    let named = palette.light.or(palette.dark)
+        .map(|variant| variant.named)
+        .ok_or_else(|| format!("{} defines no GTK colors for borders", theme))?;
+    let color = |name: &str| named.get(name).and_then(|value| value.get(..7)).map(|hex| hex.to_string());
+
+    let active = color("accent_bg_color").ok_or_else(|| format!("{} has no accent color", theme))?;
+    let background = color("window_bg_color").unwrap_or_else(|| "#303030".to_string());
+    let foreground = color("window_fg_color").unwrap_or_else(|| "#ffffff".to_string());
+    let inactive = color("borders").unwrap_or_else(|| background.clone());
+    let urgent = color("error_color").unwrap_or_else(|| "#e01b24".to_string());
+
+    Ok(match compositor {
+        Compositor::Hyprland => vec![
+            "general {".to_string(),
+            format!("    col.active_border = rgb({})", &active[1..]),
+            format!("    col.inactive_border = rgb({})", &inactive[1..]),
+            "}".to_string(),
+        ],
+        // class border background text indicator child_border
+        _ => vec![
+            format!("client.focused {0} {0} {1} {0} {0}", active, foreground),
+            format!("client.focused_inactive {0} {1} {2} {0} {0}", inactive, background, foreground),
+            format!("client.unfocused {0} {1} {2} {0} {0}", inactive, background, foreground),
+            format!("client.urgent {0} {0} {1} {0} {0}", urgent, foreground),
+        ],
+    })
+}
+
+impl DesktopBackend for WlrootsBackend {
+    fn name(&self) -> &'static str {
+        "wlroots"
+    }
+
+    fn detect(&self) -> bool {
+        compositor() != Compositor::Other || desktop_is(&["RIVER", "WAYFIRE", "LABWC", "WLROOTS"])
+    }
+
+    // settings.ini covers GTK 3 and GTK 4, gsettings only reaches apps using the portal
+    fn apply_gtk(&self, theme: &str) -> ApplyResult {
+        set_gtk_setting("gtk-theme-name", theme)?;
+        try_gsettings("gtk-theme", theme);
+        Ok(())
+    }
+
+    // The compositor draws the borders, take their colors from the GTK theme
+    fn apply_window_manager(&self, theme: &str) -> ApplyResult {
+        let compositor = compositor();
+        if compositor == Compositor::Other {
+            return Err(ApplyError::Unsupported);
+        }
+        Ok(write_block("colors", theme, border_lines(compositor, theme)?)?)
+    }
+
+    fn apply_icons(&self, theme: &str) -> ApplyResult {
+        set_gtk_setting("gtk-icon-theme-name", theme)?;
+        try_gsettings("icon-theme", theme);
+        Ok(())
+    }
+
+    // environment.d covers clients of new sessions, the include file and hyprctl the running one
+    // (sway exports XCURSOR_THEME/SIZE to what it starts once the seat is configured)
+    fn apply_cursor(&self, theme: &str, size: u32) -> ApplyResult {
+        write_cursor_environment(theme, size)?;
+        let size = size.to_string();
+        set_gtk_setting("gtk-cursor-theme-name", theme)?;
+        set_gtk_setting("gtk-cursor-theme-size", &size)?;
+        try_gsettings("cursor-theme", theme);
+
+        let lines = match compositor() {
+            Compositor::Hyprland => {
+                if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
+                    let _ = run("hyprctl", &["setcursor", theme, &size]);
+                }
+                vec![format!("env = XCURSOR_THEME,{}", theme), format!("env = XCURSOR_SIZE,{}", size)]
+            }
+            Compositor::Sway => vec![format!("seat * xcursor_theme {} {}", theme, size)],
+            Compositor::Other => return Ok(()),
+        };
+        Ok(write_block("cursor", theme, lines)?)
+    }
+
+    // GTK has one font setting, sway also uses it for title bars
+    fn apply_fonts(&self, fonts: &ThemeFonts) -> ApplyResult {
+        let font = match &fonts.interface {
+            Some(font) => font_with_size(font, get_gtk_setting("gtk-font-name").as_deref()),
+            None => return Err(ApplyError::Unsupported),
+        };
+        set_gtk_setting("gtk-font-name", &font)?;
+        try_gsettings("font-name", &font);
+        if compositor() == Compositor::Sway {
+            write_block("font", &font, vec![format!("font pango:{}", font)])?;
+        }
+        Ok(())
+    }
+
+    fn apply_color_scheme(&self, scheme: ColorScheme) -> ApplyResult {
+        let prefer_dark = match scheme {
+            ColorScheme::Dark => "1",
+            ColorScheme::Light | ColorScheme::Default => "0",
+        };
+        set_gtk_setting("gtk-application-prefer-dark-theme", prefer_dark)?;
+        let _ = set_portal_color_scheme(scheme);
+        Ok(())
+    }
+
+    fn snapshot(&self) -> AppearanceSnapshot {
+        let mut snapshot = AppearanceSnapshot { desktop: self.name().to_string(), ..Default::default() };
+        let entries = [
+            (GTK, "gtk-theme-name"),
+            (ICONS, "gtk-icon-theme-name"),
+            (CURSOR, "gtk-cursor-theme-name"),
+            (CURSOR_SIZE, "gtk-cursor-theme-size"),
+            (FONT_INTERFACE, "gtk-font-name"),
+        ];
+        for (key, setting) in entries {
+            if let Some(value) = get_gtk_setting(setting) {
+                snapshot.values.insert(key.to_string(), value);
+            }
+        }
+        if let Some(theme) = block_value("colors") {
+            snapshot.values.insert(WINDOW_MANAGER.to_string(), theme);
+        }
+        let scheme = match get_gtk_setting("gtk-application-prefer-dark-theme").as_deref() {
+            Some("1") | Some("true") => Some(ColorScheme::Dark),
+            Some(_) => Some(ColorScheme::Light),
+            None => None,
+        };
+        if let Some(scheme) = scheme {
+            snapshot.values.insert(COLOR_SCHEME.to_string(), color_scheme_name(scheme).to_string());
+        }
+        if let Ok((_, include, _)) = compositor_config(compositor()) {
+            let content = fs::read_to_string(include).unwrap_or_default();
+            snapshot.values.insert(INCLUDE_BLOCKS.to_string(), if content.is_empty() { UNSET.to_string() } else { content });
+        }
+        snapshot
+    }
+
+    // Applying the values rewrites single blocks, the include file then goes back as a whole
+    fn restore(&self, snapshot: &AppearanceSnapshot) -> Vec<ComponentReport> {
+        let mut reports = restore_values(self, snapshot);
+        if let Some(content) = snapshot.values.get(INCLUDE_BLOCKS) {
+            reports.push(report("Compositor config", restore_include(content).map_err(ApplyError::from)));
+        }
+        reports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_include_blocks() {
+        let file = std::env::temp_dir().join(format!("reskin-wlroots-{}.conf", std::process::id()));
+        let content = format!(
+            "{}\n\n{}colors Nordic\nclient.focused #88c0d0 #88c0d0 #2e3440 #88c0d0 #88c0d0\n\n{}cursor Bibata\nseat * xcursor_theme Bibata 24\n",
+            INCLUDE_HEADER, BLOCK_PREFIX, BLOCK_PREFIX
+        );
+        fs::write(&file, content).unwrap();
+        let blocks = read_blocks(&file);
+        let _ = fs::remove_file(&file);
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!((blocks[0].0.as_str(), blocks[0].1.as_str()), ("colors", "Nordic"));
+        assert_eq!(blocks[0].2, ["client.focused #88c0d0 #88c0d0 #2e3440 #88c0d0 #88c0d0"]);
+        assert_eq!(blocks[1].2, ["seat * xcursor_theme Bibata 24"]);
+        assert!(read_blocks(&file).is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+// XFCE, configured through xfconf channels
+use std::path::Path;
+use crate::types::{AppearanceSnapshot, ColorScheme, ThemeFonts};
+use super::*;
+
+pub struct XfceBackend;
+
+fn xfconf_set(channel: &str, property: &str, value: &str) -> Result<(), String> {
+    // --create with a type so properties missing from a fresh profile get added
+    let value_type = if value.parse::<i64>().is_ok() { "int" } else { "string" };
+    run("xfconf-query", &["-c", channel, "-p", property, "--create", "-t", value_type, "-s", value])
+}
+
+fn xfconf_get(channel: &str, property: &str) -> Option<String> {
+    read("xfconf-query", &["-c", channel, "-p", property])
+}
+
+// Every monitor and workspace has its own last-image property
+fn wallpaper_properties() -> Vec<String> {
+    read("xfconf-query", &["-c", "xfce4-desktop", "-l"])
+        .map(|list| list.lines().filter(|p| p.ends_with("/last-image")).map(|p| p.to_string()).collect())
+        .unwrap_or_default()
+}
+
+impl DesktopBackend for XfceBackend {
+    fn name(&self) -> &'static str {
+        "XFCE"
+    }
+
+    fn detect(&self) -> bool {
+        desktop_is(&["XFCE"])
+    }
+
+    fn apply_gtk(&self, theme: &str) -> ApplyResult {
+        Ok(xfconf_set("xsettings", "/Net/ThemeName", theme)?)
+    }
+
+    fn apply_window_manager(&self, theme: &str) -> ApplyResult {
+        Ok(xfconf_set("xfwm4", "/general/theme", theme)?)
+    }
+
+    fn apply_icons(&self, theme: &str) -> ApplyResult {
+        Ok(xfconf_set("xsettings", "/Net/IconThemeName", theme)?)
+    }
+
+    fn apply_cursor(&self, theme: &str, size: u32) -> ApplyResult {
+        xfconf_set("xsettings", "/Gtk/CursorThemeName", theme)?;
+        Ok(xfconf_set("xsettings", "/Gtk/CursorThemeSize", &size.to_string())?)
+    }
+
+    // XFCE has no separate document font
+    fn apply_fonts(&self, fonts: &ThemeFonts) -> ApplyResult {
+        if fonts.interface.is_none() && fonts.monospace.is_none() {
+            return Err(ApplyError::Unsupported);
+        }
+        let properties = [("/Gtk/FontName", &fonts.interface), ("/Gtk/MonospaceFontName", &fonts.monospace)];
+        for (property, font) in properties {
+            if let Some(font) = font {
+                let current = xfconf_get("xsettings", property);
+                xfconf_set("xsettings", property, &font_with_size(font, current.as_deref()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_wallpaper(&self, path: &Path) -> ApplyResult {
+        let properties = wallpaper_properties();
+        if properties.is_empty() {
+            return Err(ApplyError::Failed("No XFCE desktop backdrop found".to_string()));
+        }
+        let path = path.to_string_lossy();
+        for property in properties {
+            xfconf_set("xfce4-desktop", &property, &path)?;
+        }
+        Ok(())
+    }
+
+    // xfsettingsd has no color scheme, but the GTK portal hands this one to apps
+    fn apply_color_scheme(&self, scheme: ColorScheme) -> ApplyResult {
+        Ok(set_portal_color_scheme(scheme)?)
+    }
+
+    fn snapshot(&self) -> AppearanceSnapshot {
+        let mut snapshot = AppearanceSnapshot { desktop: self.name().to_string(), ..Default::default() };
+        let entries = [
+            (GTK, "xsettings", "/Net/ThemeName"),
+            (WINDOW_MANAGER, "xfwm4", "/general/theme"),
+            (ICONS, "xsettings", "/Net/IconThemeName"),
+            (CURSOR, "xsettings", "/Gtk/CursorThemeName"),
+            (CURSOR_SIZE, "xsettings", "/Gtk/CursorThemeSize"),
+            (FONT_INTERFACE, "xsettings", "/Gtk/FontName"),
+            (FONT_MONOSPACE, "xsettings", "/Gtk/MonospaceFontName"),
+        ];
+        for (key, channel, property) in entries {
+            if let Some(value) = xfconf_get(channel, property) {
+                snapshot.values.insert(key.to_string(), value);
+            }
+        }
+        if let Some(wallpaper) = wallpaper_properties().first().and_then(|p| xfconf_get("xfce4-desktop", p)) {
+            snapshot.values.insert(WALLPAPER.to_string(), wallpaper);
+        }
+        if let Some(scheme) = get_portal_color_scheme() {
+            snapshot.values.insert(COLOR_SCHEME.to_string(), color_scheme_name(scheme).to_string());
+        }
+        snapshot
+    }
+}
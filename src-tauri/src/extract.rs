@@ -1,8 +1,32 @@
 use std::fs;
 use std::fs::File;
 use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use tauri::{AppHandle, State};
+use crate::jobs::JobManager;
+use crate::paths::themes_dir;
+use crate::progress::Progress;
 use crate::types::ThemeManifest;
 
+// Bundle asset and theme names become file names, so they must be exactly one normal
+// path component: no separators, no "..", nothing absolute
+pub fn is_plain_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    !name.contains('/') && matches!((components.next(), components.next()), (Some(Component::Normal(_)), None))
+}
+
+// Lengths come from the bundle, so they are checked against what is left of it before allocating
+fn read_bytes(file: &mut File, len: usize, remaining: &mut u64, what: &str) -> Result<Vec<u8>, String> {
+    if len as u64 > *remaining {
+        return Err(format!("Invalid bundle: {} is longer than the rest of the file", what));
+    }
+    let mut data = vec![0u8; len];
+    file.read_exact(&mut data)
+        .map_err(|e| format!("Failed to read {}: {}", what, e))?;
+    *remaining -= len as u64;
+    Ok(data)
+}
+
 #[tauri::command]
 pub fn extract_theme_info(file_data: Vec<u8>) -> Result<ThemeManifest, String> {
     // Read the RSKN header and extract manifest
@@ -22,7 +46,8 @@ pub fn extract_theme_info(file_data: Vec<u8>) -> Result<ThemeManifest, String> {
         file_data[8], file_data[9], file_data[10], file_data[11]
     ]);
 
-    if file_data.len() < 12 + manifest_size {
+    // The size comes from the bundle, 12 + manifest_size could overflow
+    if manifest_size > file_data.len() - 12 {
         return Err("Invalid .reskin file: manifest size mismatch".to_string());
     }
 
@@ -44,13 +69,25 @@ pub fn extract_theme_info_from_file(file_path: String) -> Result<ThemeManifest,
     }
 }
 
-#[tauri::command]
-pub fn extract_theme(bundle_path: String) -> Result<String, String> {
-    use std::path::Path;
+#[tauri::command(async)]
+pub fn extract_theme(app: AppHandle, jobs: State<'_, JobManager>, bundle_path: String) -> Result<String, String> {
+    let mut progress = Progress::new(&app, &jobs, "extract", &bundle_path);
+    let result = extract_theme_to(&bundle_path, &themes_dir()?, &mut progress);
+    progress.finish(&result);
+
+    let (_, output_dir) = result?;
+    Ok(format!("Theme extracted to {}", output_dir.display()))
+}
 
-    let mut file = File::open(&bundle_path) // Attempt to open file
+// Extract a bundle into <dest_root>/<theme name>, returning the manifest and output directory
+pub fn extract_theme_to(bundle_path: &str, dest_root: &Path, progress: &mut Progress) -> Result<(ThemeManifest, PathBuf), String> {
+    let mut file = File::open(bundle_path) // Attempt to open file
         .map_err(|e| format!("Failed to open bundle: {}", e))?; // Throw error
 
+    // The format has no asset count, so only bytes are known up front
+    let bundle_size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    progress.phase("extracting", bundle_size, 0);
+
     let mut magic = [0u8; 4]; // RSKN magic number as bytes
     file.read_exact(&mut magic) // Read magic number from file
         .map_err(|e| format!("Failed to read magic: {}", e))?;
@@ -63,46 +100,76 @@ pub fn extract_theme(bundle_path: String) -> Result<String, String> {
         .map_err(|e| format!("Failed to reach manifest length: {}", e))?;
     let manifest_len = usize::from_le_bytes(len_bytes);
 
-    let mut manifest_json = vec![0u8; manifest_len];
-    file.read_exact(&mut manifest_json)
-        .map_err(|e| format!("Failed to read manifest data: {}", e))?;
+    let mut remaining = bundle_size.saturating_sub(12);
+    let manifest_json = read_bytes(&mut file, manifest_len, &mut remaining, "manifest data")?;
 
     let manifest: ThemeManifest = serde_json::from_slice(&manifest_json)
         .map_err(|e| format!("Failed to parse manifest: {}", e))?;
     
-    let home_dir = std::env::var("HOME").unwrap_or("/home/user".into()); // Unwrap ~ into /home
-    let output_dir = format!("/{}/.themes/{}", home_dir, manifest.name); // Extraction output directory
+    if !is_plain_name(&manifest.name) {
+        return Err(format!("Invalid theme name in manifest: {}", manifest.name));
+    }
+    let output_dir = dest_root.join(&manifest.name); // Extraction output directory
 
+    progress.track_cleanup(output_dir.clone()); // Only if this extraction creates it
     fs::create_dir_all(&output_dir) // Create output directory and all necessary parent directories
         .map_err(|e| format!("Failed to create output dir: {}", e))?;
     
-    fs::write(Path::new(&output_dir).join("reskin.json"), &manifest_json) // Write reskin.json file into output directory
+    fs::write(output_dir.join("reskin.json"), &manifest_json) // Write reskin.json file into output directory
         .map_err(|e| format!("Failed to write reskin.json: {}", e))?;
 
     // Extract assets from the bundle file
     loop {
+        progress.check_cancelled()?;
         let mut filename_len_bytes = [0u8; 4];
         if file.read_exact(&mut filename_len_bytes).is_err() {
             break; // End of file
         }
+        remaining = remaining.saturating_sub(4);
         let filename_len = u32::from_le_bytes(filename_len_bytes) as usize;
-        let mut filename_bytes = vec![0u8; filename_len];
-        file.read_exact(&mut filename_bytes)
-            .map_err(|e| format!("Failed to read filename: {}", e))?;
+        let filename_bytes = read_bytes(&mut file, filename_len, &mut remaining, "filename")?;
         let filename = String::from_utf8_lossy(&filename_bytes).to_string();
 
         let mut asset_len_bytes = [0u8; 4];
         file.read_exact(&mut asset_len_bytes)
             .map_err(|e| format!("Failed to read asset length: {}", e))?;
+        remaining = remaining.saturating_sub(4);
         let asset_len = u32::from_le_bytes(asset_len_bytes) as usize;
-        let mut asset_data = vec![0u8; asset_len];
-        file.read_exact(&mut asset_data)
-            .map_err(|e| format!("Failed to read asset data: {}", e))?;
+        let asset_data = read_bytes(&mut file, asset_len, &mut remaining, "asset data")?;
 
-        let out_path = Path::new(&output_dir).join(&filename);
+        if !is_plain_name(&filename) {
+            return Err(format!("Invalid asset name in bundle: {}", filename));
+        }
+        progress.set_file(&filename);
+        let out_path = output_dir.join(&filename);
         fs::write(out_path, &asset_data)
             .map_err(|e| format!("Failed to write asset {}: {}", filename, e))?;
+        progress.add((8 + filename_len + asset_len) as u64, 1);
     }
 
-    Ok(format!("Theme extracted to {}", output_dir))
-}
\ No newline at end of file
+    Ok((manifest, output_dir))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_names() {
+        for name in ["gtk.css", "Nordic", "index.theme", "..theme", "a..b"] {
+            assert!(is_plain_name(name), "{}", name);
+        }
+        for name in ["", ".", "..", "/etc/passwd", "../x", "a/b", "a/", "./a"] {
+            assert!(!is_plain_name(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_manifest_lengths() {
+        for size in [u64::MAX, u64::MAX - 11, 3] {
+            let mut data = b"RSKN".to_vec();
+            data.extend(size.to_le_bytes());
+            data.extend(b"{}");
+            assert!(extract_theme_info(data).is_err(), "{}", size);
+        }
+    }
+}
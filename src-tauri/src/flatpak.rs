@@ -0,0 +1,376 @@
+// Flatpak integration: expose reskin's theme directories to sandboxed apps
+// through the user's global override keyfile
+use std::fs;
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use crate::paths::{data_home, reskin_config_dir, themes_dir, icons_dir, fonts_dir, home_dir};
+use crate::settings::{load_settings, save_settings};
+
+// Minimal GKeyFile editor that keeps comments, ordering and unknown keys intact
+enum Line {
+    Raw(String),
+    Entry(String, String),
+}
+
+struct Group {
+    name: String,
+    lines: Vec<Line>,
+}
+
+#[derive(Default)]
+struct KeyFile {
+    header: Vec<Line>,
+    groups: Vec<Group>,
+}
+
+impl KeyFile {
+    fn parse(content: &str) -> KeyFile {
+        let mut keyfile = KeyFile::default();
+
+        for raw in content.lines() {
+            let trimmed = raw.trim();
+            if trimmed.starts_with('[') && trimmed.ends_with(']') {
+                keyfile.groups.push(Group { name: trimmed[1..trimmed.len() - 1].to_string(), lines: Vec::new() });
+                continue;
+            }
+
+            let line = match trimmed.split_once('=') {
+                Some((key, value)) if !trimmed.starts_with('#') => {
+                    Line::Entry(key.trim().to_string(), value.trim().to_string())
+                }
+                _ => Line::Raw(raw.to_string()),
+            };
+
+            match keyfile.groups.last_mut() {
+                Some(group) => group.lines.push(line),
+                None => keyfile.header.push(line),
+            }
+        }
+
+        keyfile
+    }
+
+    fn get(&self, group: &str, key: &str) -> Option<&str> {
+        self.groups.iter()
+            .filter(|g| g.name == group)
+            .flat_map(|g| g.lines.iter())
+            .find_map(|line| match line {
+                Line::Entry(k, v) if k == key => Some(v.as_str()),
+                _ => None,
+            })
+    }
+
+    fn set(&mut self, group: &str, key: &str, value: &str) {
+        if !self.groups.iter().any(|g| g.name == group) {
+            self.groups.push(Group { name: group.to_string(), lines: Vec::new() });
+        }
+        let group = self.groups.iter_mut().find(|g| g.name == group).unwrap();
+
+        for line in group.lines.iter_mut() {
+            if let Line::Entry(k, v) = line {
+                if k == key {
+                    *v = value.to_string();
+                    return;
+                }
+            }
+        }
+        group.lines.push(Line::Entry(key.to_string(), value.to_string()));
+    }
+
+    fn remove(&mut self, group: &str, key: &str) {
+        for g in self.groups.iter_mut().filter(|g| g.name == group) {
+            g.lines.retain(|line| !matches!(line, Line::Entry(k, _) if k == key));
+        }
+    }
+
+    fn serialize(&self) -> String {
+        let mut out = String::new();
+        let write_lines = |out: &mut String, lines: &[Line]| {
+            for line in lines {
+                match line {
+                    Line::Raw(raw) => out.push_str(raw),
+                    Line::Entry(k, v) => out.push_str(&format!("{}={}", k, v)),
+                }
+                out.push('\n');
+            }
+        };
+
+        write_lines(&mut out, &self.header);
+        for group in &self.groups {
+            out.push_str(&format!("[{}]\n", group.name));
+            write_lines(&mut out, &group.lines);
+        }
+        out
+    }
+}
+
+// What reskin changed in the override file, so revert only undoes our own edits
+#[derive(Serialize, Deserialize, Default)]
+struct FlatpakState {
+    added_filesystems: Vec<String>,
+    previous_env: Vec<(String, Option<String>)>,
+}
+
+fn overrides_file() -> Result<PathBuf, String> {
+    Ok(data_home()?.join("flatpak/overrides/global"))
+}
+
+fn state_file() -> Result<PathBuf, String> {
+    Ok(reskin_config_dir()?.join("flatpak.json"))
+}
+
+// Everything apply and revert touch, recorded by snapshots
+pub fn override_files() -> Vec<PathBuf> {
+    [overrides_file(), state_file()].into_iter().filter_map(Result::ok).collect()
+}
+
+fn load_state() -> FlatpakState {
+    state_file()
+        .ok()
+        .and_then(|file| fs::read_to_string(file).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(state: &FlatpakState) -> Result<(), String> {
+    let config_dir = reskin_config_dir()?;
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    let json = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize Flatpak state: {}", e))?;
+    fs::write(state_file()?, json)
+        .map_err(|e| format!("Failed to write Flatpak state: {}", e))
+}
+
+fn load_overrides() -> Result<KeyFile, String> {
+    let file = overrides_file()?;
+    if !file.exists() {
+        return Ok(KeyFile::default());
+    }
+    let content = fs::read_to_string(&file)
+        .map_err(|e| format!("Failed to read Flatpak overrides: {}", e))?;
+    Ok(KeyFile::parse(&content))
+}
+
+fn save_overrides(keyfile: &KeyFile) -> Result<(), String> {
+    let file = overrides_file()?;
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create Flatpak overrides directory: {}", e))?;
+    }
+    fs::write(&file, keyfile.serialize())
+        .map_err(|e| format!("Failed to write Flatpak overrides: {}", e))
+}
+
+// Express a directory the way flatpak expects it in filesystems=
+fn filesystem_entry(dir: PathBuf) -> Result<String, String> {
+    let data_home = data_home()?;
+    let home = home_dir()?;
+
+    let entry = if let Ok(rel) = dir.strip_prefix(&data_home) {
+        format!("xdg-data/{}", rel.display())
+    } else if let Ok(rel) = dir.strip_prefix(&home) {
+        format!("~/{}", rel.display())
+    } else {
+        dir.to_string_lossy().to_string()
+    };
+
+    Ok(format!("{}:ro", entry))
+}
+
+fn split_list(value: Option<&str>) -> Vec<String> {
+    value.unwrap_or("")
+        .split(';')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn join_list(items: &[String]) -> String {
+    format!("{};", items.join(";"))
+}
+
+// Flatpak reserves /usr and refuses /usr/share/themes or /usr/share/icons as
+// filesystems= entries, host-os exposes both read-only under /run/host/usr
+const SYSTEM_FILESYSTEM: &str = "host-os:ro";
+
+// Add read access to the theme, icon and font directories, plus the system ones
+// when a theme was installed for all users
+fn grant_filesystems(keyfile: &mut KeyFile, state: &mut FlatpakState, system: bool) -> Result<(), String> {
+    let mut entries = Vec::new();
+    for dir in [themes_dir()?, icons_dir()?, fonts_dir()?] {
+        entries.push(filesystem_entry(dir)?);
+    }
+    if system {
+        entries.push(SYSTEM_FILESYSTEM.to_string());
+    }
+    add_filesystems(keyfile, state, entries);
+    Ok(())
+}
+
+fn add_filesystems(keyfile: &mut KeyFile, state: &mut FlatpakState, entries: Vec<String>) {
+    let mut filesystems = split_list(keyfile.get("Context", "filesystems"));
+    for entry in entries {
+        if !filesystems.contains(&entry) {
+            filesystems.push(entry.clone());
+            state.added_filesystems.push(entry);
+        }
+    }
+    keyfile.set("Context", "filesystems", &join_list(&filesystems));
+}
+
+// Point sandboxed apps at the theme, remembering the user's own value only the
+// first time reskin touches a key
+fn set_env(keyfile: &mut KeyFile, state: &mut FlatpakState, key: &str, value: &str) {
+    if !state.previous_env.iter().any(|(k, _)| k == key) {
+        let previous = keyfile.get("Environment", key).map(|v| v.to_string());
+        state.previous_env.push((key.to_string(), previous));
+    }
+    keyfile.set("Environment", key, value);
+}
+
+// Drop only the filesystems reskin added and put back the user's environment
+fn revert_keyfile(keyfile: &mut KeyFile, state: &FlatpakState) {
+    let filesystems: Vec<String> = split_list(keyfile.get("Context", "filesystems"))
+        .into_iter()
+        .filter(|entry| !state.added_filesystems.contains(entry))
+        .collect();
+    if filesystems.is_empty() {
+        keyfile.remove("Context", "filesystems");
+    } else {
+        keyfile.set("Context", "filesystems", &join_list(&filesystems));
+    }
+
+    for (key, previous) in &state.previous_env {
+        match previous {
+            Some(value) => keyfile.set("Environment", key, value),
+            None => keyfile.remove("Environment", key),
+        }
+    }
+}
+
+// Run after install: make installed themes readable inside the sandbox
+pub fn grant_access(system: bool) -> Result<String, String> {
+    let mut keyfile = load_overrides()?;
+    let mut state = load_state();
+
+    grant_filesystems(&mut keyfile, &mut state, system)?;
+
+    save_overrides(&keyfile)?;
+    save_state(&state)?;
+
+    Ok("Flatpak access".to_string())
+}
+
+// Run after apply: grant access and point sandboxed apps at the theme
+// GTK takes the icon theme from the settings portal once the directory is readable
+pub fn apply_overrides(gtk_theme: &str, system: bool) -> Result<String, String> {
+    let mut keyfile = load_overrides()?;
+    let mut state = load_state();
+
+    grant_filesystems(&mut keyfile, &mut state, system)?;
+    set_env(&mut keyfile, &mut state, "GTK_THEME", gtk_theme);
+
+    save_overrides(&keyfile)?;
+    save_state(&state)?;
+
+    Ok("Flatpak overrides".to_string())
+}
+
+// Undo everything apply_overrides changed, leaving the user's own overrides alone
+pub fn revert_overrides() -> Result<String, String> {
+    let state = load_state();
+    let mut keyfile = load_overrides()?;
+
+    revert_keyfile(&mut keyfile, &state);
+
+    save_overrides(&keyfile)?;
+    save_state(&FlatpakState::default())?;
+
+    Ok("Flatpak overrides reverted".to_string())
+}
+
+#[tauri::command]
+pub fn get_flatpak_integration() -> Result<bool, String> {
+    Ok(load_settings().flatpak_integration)
+}
+
+#[tauri::command]
+pub fn set_flatpak_integration(enabled: bool) -> Result<(), String> {
+    let mut settings = load_settings();
+    settings.flatpak_integration = enabled;
+    save_settings(&settings)?;
+
+    // Turning the integration off also removes what it wrote
+    if !enabled {
+        revert_overrides()?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn revert_flatpak_overrides() -> Result<String, String> {
+    revert_overrides()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OVERRIDES: &str = "# user overrides\n[Context]\nfilesystems=~/Music:ro;\nsockets=wayland;\n\n[Environment]\nGTK_THEME=Adwaita\nFOO=bar\n";
+
+    #[test]
+    fn keyfile_round_trips_unchanged() {
+        assert_eq!(KeyFile::parse(OVERRIDES).serialize(), OVERRIDES);
+    }
+
+    #[test]
+    fn keyfile_get_set_remove() {
+        let mut keyfile = KeyFile::parse(OVERRIDES);
+        assert_eq!(keyfile.get("Context", "sockets"), Some("wayland;"));
+        assert_eq!(keyfile.get("Environment", "sockets"), None);
+
+        keyfile.set("Environment", "GTK_THEME", "Nordic");
+        keyfile.set("Environment", "NEW", "1");
+        keyfile.set("Policy", "x", "y");
+        keyfile.remove("Environment", "FOO");
+
+        assert_eq!(keyfile.serialize(), "# user overrides\n[Context]\nfilesystems=~/Music:ro;\nsockets=wayland;\n\n[Environment]\nGTK_THEME=Nordic\nNEW=1\n[Policy]\nx=y\n");
+    }
+
+    #[test]
+    fn keyfile_keeps_comments_with_equals() {
+        let keyfile = KeyFile::parse("[Context]\n# a=b\n");
+        assert_eq!(keyfile.get("Context", "# a"), None);
+        assert_eq!(keyfile.serialize(), "[Context]\n# a=b\n");
+    }
+
+    #[test]
+    fn revert_undoes_only_reskin_changes() {
+        let mut keyfile = KeyFile::parse(OVERRIDES);
+        let mut state = FlatpakState::default();
+        add_filesystems(&mut keyfile, &mut state, vec!["~/Music:ro".to_string(), "~/.themes:ro".to_string(), SYSTEM_FILESYSTEM.to_string()]);
+        set_env(&mut keyfile, &mut state, "GTK_THEME", "Nordic");
+        set_env(&mut keyfile, &mut state, "GTK_THEME", "Dracula");
+        set_env(&mut keyfile, &mut state, "OTHER", "1");
+
+        assert_eq!(keyfile.get("Context", "filesystems"), Some("~/Music:ro;~/.themes:ro;host-os:ro;"));
+        assert_eq!(state.added_filesystems, vec!["~/.themes:ro", "host-os:ro"]);
+        assert_eq!(keyfile.get("Environment", "GTK_THEME"), Some("Dracula"));
+
+        revert_keyfile(&mut keyfile, &state);
+        assert_eq!(keyfile.serialize(), OVERRIDES);
+    }
+
+    #[test]
+    fn revert_removes_emptied_filesystems() {
+        let mut keyfile = KeyFile::default();
+        let mut state = FlatpakState::default();
+        add_filesystems(&mut keyfile, &mut state, vec!["~/.themes:ro".to_string()]);
+        set_env(&mut keyfile, &mut state, "GTK_THEME", "Nordic");
+
+        revert_keyfile(&mut keyfile, &state);
+        assert_eq!(keyfile.get("Context", "filesystems"), None);
+        assert_eq!(keyfile.get("Environment", "GTK_THEME"), None);
+    }
+}
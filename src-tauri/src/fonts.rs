@@ -0,0 +1,457 @@
+// Font discovery: find the fonts bundled with a theme and read their sfnt tables
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::paths::fonts_dir;
+use crate::types::{FontAxis, FontCollision, FontFamily, FontScan};
+
+// Only sfnt fonts, fontconfig doesn't use EOT or WOFF for desktop apps
+pub const FONT_EXTENSIONS: [&str; 4] = ["ttf", "otf", "ttc", "otc"];
+
+// A single face inside a font file (collections hold several)
+pub struct FontFace {
+    pub path: PathBuf,
+    pub family: String,
+    pub style: String,
+    pub monospace: bool,
+    pub axes: Vec<FontAxis>,
+}
+
+pub fn is_font_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| FONT_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+// Collect font files at any depth, e.g. fonts/Inter/*.ttf
+pub fn find_font_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    collect_font_files(dir, &mut files);
+    files.sort();
+    files
+}
+
+fn collect_font_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // file_type() doesn't follow symlinks, so link loops can't recurse forever
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => collect_font_files(&path, files),
+                Ok(_) if is_font_file(&path) => files.push(path),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+// 16.16 fixed point
+fn read_fixed(data: &[u8], offset: usize) -> Option<f32> {
+    read_u32(data, offset).map(|v| v as i32 as f32 / 65536.0)
+}
+
+// Offsets of each font in the file, collections start with a 'ttcf' header
+fn font_offsets(data: &[u8]) -> Vec<usize> {
+    if data.starts_with(b"ttcf") {
+        // A bogus count stops at the end of the data instead of looping billions of times
+        let count = read_u32(data, 8).unwrap_or(0) as usize;
+        (0..count)
+            .map_while(|i| read_u32(data, 12 + i * 4).map(|o| o as usize))
+            .collect()
+    } else {
+        vec![0]
+    }
+}
+
+fn find_table<'a>(data: &'a [u8], font_offset: usize, tag: &[u8; 4]) -> Option<&'a [u8]> {
+    let num_tables = read_u16(data, font_offset + 4)? as usize;
+    (0..num_tables).find_map(|i| {
+        let record = font_offset + 12 + i * 16;
+        if data.get(record..record + 4)? != tag {
+            return None;
+        }
+        let offset = read_u32(data, record + 8)? as usize;
+        let length = read_u32(data, record + 12)? as usize;
+        data.get(offset..offset + length)
+    })
+}
+
+fn decode_name(platform: u16, encoding: u16, bytes: &[u8]) -> Option<String> {
+    match (platform, encoding) {
+        // Unicode and Windows platforms store UTF-16BE
+        (0, _) | (3, 0) | (3, 1) | (3, 10) => {
+            let units = bytes.chunks_exact(2).map(|b| u16::from_be_bytes([b[0], b[1]]));
+            Some(char::decode_utf16(units).map(|c| c.unwrap_or('\u{FFFD}')).collect())
+        }
+        // Mac Roman, close enough to Latin-1 for family names
+        (1, 0) => Some(bytes.iter().map(|&b| b as char).collect()),
+        _ => None,
+    }
+}
+
+// Read family and style, preferring the typographic names (16/17) and US English
+fn read_names(name: &[u8]) -> (Option<String>, Option<String>) {
+    let count = read_u16(name, 2).unwrap_or(0) as usize;
+    let strings = read_u16(name, 4).unwrap_or(0) as usize;
+    let mut best: [(u8, Option<String>); 4] = Default::default(); // name ids 1, 2, 16, 17
+
+    for i in 0..count {
+        let record = 6 + i * 12;
+        let (Some(platform), Some(encoding), Some(language), Some(name_id), Some(length), Some(offset)) = (
+            read_u16(name, record), read_u16(name, record + 2), read_u16(name, record + 4),
+            read_u16(name, record + 6), read_u16(name, record + 8), read_u16(name, record + 10),
+        ) else {
+            break;
+        };
+
+        let slot = match name_id { 1 => 0, 2 => 1, 16 => 2, 17 => 3, _ => continue };
+        let score = match (platform, language) { (3, 0x409) => 3, (3, _) | (0, _) => 2, _ => 1 };
+        if score <= best[slot].0 {
+            continue;
+        }
+
+        let start = strings + offset as usize;
+        if let Some(value) = name.get(start..start + length as usize).and_then(|b| decode_name(platform, encoding, b)) {
+            best[slot] = (score, Some(value));
+        }
+    }
+
+    let [family, style, typo_family, typo_style] = best.map(|(_, value)| value);
+    (typo_family.or(family), typo_style.or(style))
+}
+
+// Variation axes from the 'fvar' table
+fn read_axes(fvar: &[u8]) -> Vec<FontAxis> {
+    let axes_offset = read_u16(fvar, 4).unwrap_or(0) as usize;
+    let axis_count = read_u16(fvar, 8).unwrap_or(0) as usize;
+    let axis_size = read_u16(fvar, 10).unwrap_or(20) as usize;
+
+    (0..axis_count)
+        .filter_map(|i| {
+            let record = axes_offset + i * axis_size;
+            Some(FontAxis {
+                tag: String::from_utf8_lossy(fvar.get(record..record + 4)?).to_string(),
+                min: read_fixed(fvar, record + 4)?,
+                default: read_fixed(fvar, record + 8)?,
+                max: read_fixed(fvar, record + 12)?,
+            })
+        })
+        .collect()
+}
+
+// Read every face in a font file, unreadable fonts yield nothing
+pub fn read_font(path: &Path) -> Vec<FontFace> {
+    match fs::read(path) {
+        Ok(data) => read_faces(&data, path),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn read_faces(data: &[u8], path: &Path) -> Vec<FontFace> {
+    font_offsets(data)
+        .into_iter()
+        .filter_map(|offset| {
+            let (family, style) = read_names(find_table(data, offset, b"name")?);
+            Some(FontFace {
+                path: path.to_path_buf(),
+                family: family?,
+                style: style.unwrap_or_else(|| "Regular".to_string()),
+                // post.isFixedPitch
+                monospace: find_table(data, offset, b"post")
+                    .and_then(|post| read_u32(post, 12))
+                    .map(|fixed| fixed != 0)
+                    .unwrap_or(false),
+                axes: find_table(data, offset, b"fvar").map(read_axes).unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+// Group faces into families in discovery order
+pub fn group_families(faces: &[FontFace]) -> Vec<FontFamily> {
+    let mut families: Vec<FontFamily> = Vec::new();
+
+    for face in faces {
+        let path = face.path.to_string_lossy().to_string();
+        let index = match families.iter().position(|f| f.name == face.family) {
+            Some(index) => index,
+            None => {
+                families.push(FontFamily {
+                    name: face.family.clone(),
+                    styles: Vec::new(),
+                    files: Vec::new(),
+                    monospace: true,
+                    axes: Vec::new(),
+                });
+                families.len() - 1
+            }
+        };
+
+        let family = &mut families[index];
+        if !family.styles.contains(&face.style) {
+            family.styles.push(face.style.clone());
+        }
+        if !family.files.contains(&path) {
+            family.files.push(path);
+        }
+        family.monospace &= face.monospace;
+        for axis in &face.axes {
+            if !family.axes.iter().any(|a| a.tag == axis.tag) {
+                family.axes.push(axis.clone());
+            }
+        }
+    }
+
+    families
+}
+
+// Faces fontconfig already knows with the same family and style, ignoring files under skip_dir
+fn find_collisions(faces: &[FontFace], skip_dir: &Path) -> Vec<FontCollision> {
+    let output = match Command::new("fc-list")
+        .arg("--format")
+        .arg("%{family[0]}\t%{style[0]}\t%{file}\n")
+        .output()
+    {
+        Ok(output) if output.status.success() => output,
+        _ => return Vec::new(), // Without fontconfig there is nothing to collide with
+    };
+
+    let listing = String::from_utf8_lossy(&output.stdout);
+    let mut seen = HashSet::new();
+    let mut collisions = Vec::new();
+
+    for line in listing.lines() {
+        let mut parts = line.splitn(3, '\t');
+        let (Some(family), Some(style), Some(file)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        if Path::new(file).starts_with(skip_dir) {
+            continue;
+        }
+
+        for face in faces {
+            if face.family.eq_ignore_ascii_case(family)
+                && face.style.eq_ignore_ascii_case(style)
+                && seen.insert((face.family.clone(), face.style.clone()))
+            {
+                collisions.push(FontCollision {
+                    family: face.family.clone(),
+                    style: face.style.clone(),
+                    installed_path: file.to_string(),
+                });
+            }
+        }
+    }
+
+    collisions
+}
+
+pub fn scan_fonts(theme_path: &Path) -> Result<FontScan, String> {
+    let theme_name = theme_path
+        .file_name()
+        .ok_or("Invalid theme path")?
+        .to_string_lossy()
+        .to_string();
+
+    let faces: Vec<FontFace> = find_font_files(theme_path)
+        .iter()
+        .flat_map(|file| read_font(file))
+        .collect();
+
+    // A reinstall shouldn't collide with the theme's own previous copy
+    let own_dir = fonts_dir()?.join(&theme_name);
+
+    Ok(FontScan {
+        families: group_families(&faces),
+        collisions: find_collisions(&faces, &own_dir),
+    })
+}
+
+#[tauri::command]
+pub fn discover_fonts(theme_path: String) -> Result<FontScan, String> {
+    let path = Path::new(&theme_path);
+    if !path.exists() {
+        return Err(format!("Theme not found at '{}'", theme_path));
+    }
+    scan_fonts(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sfnt(tables: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut data = vec![0, 1, 0, 0];
+        data.extend((tables.len() as u16).to_be_bytes());
+        data.extend([0; 6]);
+        let mut offset = 12 + tables.len() * 16;
+        for (tag, table) in tables {
+            data.extend(*tag);
+            data.extend([0; 4]);
+            data.extend((offset as u32).to_be_bytes());
+            data.extend((table.len() as u32).to_be_bytes());
+            offset += table.len();
+        }
+        for (_, table) in tables {
+            data.extend(table);
+        }
+        data
+    }
+
+    // (platform, encoding, language, name id, value)
+    fn name_table(records: &[(u16, u16, u16, u16, &str)]) -> Vec<u8> {
+        let encoded: Vec<Vec<u8>> = records.iter()
+            .map(|&(platform, _, _, _, value)| match platform {
+                1 => value.bytes().collect(),
+                _ => value.encode_utf16().flat_map(|u| u.to_be_bytes()).collect(),
+            })
+            .collect();
+        let mut table = vec![0, 0];
+        table.extend((records.len() as u16).to_be_bytes());
+        table.extend((6 + records.len() as u16 * 12).to_be_bytes());
+        let mut offset = 0;
+        for (&(platform, encoding, language, name_id, _), bytes) in records.iter().zip(&encoded) {
+            for value in [platform, encoding, language, name_id, bytes.len() as u16, offset] {
+                table.extend(value.to_be_bytes());
+            }
+            offset += bytes.len() as u16;
+        }
+        table.extend(encoded.concat());
+        table
+    }
+
+    fn post_table(fixed_pitch: bool) -> Vec<u8> {
+        let mut table = vec![0; 32];
+        table[15] = fixed_pitch as u8;
+        table
+    }
+
+    fn fvar_table(axes: &[(&[u8; 4], f32, f32, f32)]) -> Vec<u8> {
+        let mut table = Vec::new();
+        for value in [1, 0, 16, 2, axes.len() as u16, 20, 0, 0] {
+            table.extend(value.to_be_bytes());
+        }
+        for (tag, min, default, max) in axes {
+            table.extend(*tag);
+            for value in [min, default, max] {
+                table.extend(((value * 65536.0) as i32).to_be_bytes());
+            }
+            table.extend([0; 4]);
+        }
+        table
+    }
+
+    fn faces(data: &[u8]) -> Vec<FontFace> {
+        read_faces(data, Path::new("font.ttf"))
+    }
+
+    fn faces_family(data: &[u8]) -> Option<String> {
+        faces(data).into_iter().next().map(|face| face.family)
+    }
+
+    #[test]
+    fn prefers_typographic_english_names() {
+        let data = sfnt(&[(b"name", name_table(&[
+            (1, 0, 0, 1, "Mac Family"),
+            (3, 1, 0x407, 1, "Familie"),
+            (3, 1, 0x409, 1, "Inter Display"),
+            (3, 1, 0x409, 2, "Bold"),
+            (3, 1, 0x409, 16, "Inter"),
+            (3, 1, 0x409, 17, "Display Bold"),
+        ]))]);
+        let found = faces(&data);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].family, "Inter");
+        assert_eq!(found[0].style, "Display Bold");
+    }
+
+    #[test]
+    fn falls_back_to_mac_names_and_regular() {
+        let data = sfnt(&[(b"name", name_table(&[(1, 0, 0, 1, "Charter")]))]);
+        let found = faces(&data);
+        assert_eq!(found[0].family, "Charter");
+        assert_eq!(found[0].style, "Regular");
+        assert!(!found[0].monospace);
+        assert!(found[0].axes.is_empty());
+    }
+
+    #[test]
+    fn reads_post_and_fvar() {
+        let data = sfnt(&[
+            (b"name", name_table(&[(3, 1, 0x409, 1, "Mono")])),
+            (b"post", post_table(true)),
+            (b"fvar", fvar_table(&[(b"wght", 100.0, 400.0, 900.0), (b"slnt", -10.0, 0.0, 0.0)])),
+        ]);
+        let face = &faces(&data)[0];
+        assert!(face.monospace);
+        assert_eq!(face.axes.len(), 2);
+        assert_eq!((face.axes[0].tag.as_str(), face.axes[0].min, face.axes[0].default, face.axes[0].max), ("wght", 100.0, 400.0, 900.0));
+        assert_eq!((face.axes[1].tag.as_str(), face.axes[1].min), ("slnt", -10.0));
+    }
+
+    #[test]
+    fn truncated_fonts_never_panic() {
+        let data = sfnt(&[
+            (b"name", name_table(&[(3, 1, 0x409, 1, "Mono"), (3, 1, 0x409, 2, "Italic")])),
+            (b"post", post_table(true)),
+            (b"fvar", fvar_table(&[(b"wght", 100.0, 400.0, 900.0)])),
+        ]);
+        for len in 0..data.len() {
+            // Tables running past the end of the file are skipped as a whole
+            assert!(faces(&data[..len]).iter().all(|face| face.family == "Mono" && face.axes.is_empty()));
+        }
+    }
+
+    #[test]
+    fn malformed_tables_are_skipped() {
+        // A name record pointing past the string storage, and one with an odd UTF-16 length
+        let mut name = name_table(&[(3, 1, 0x409, 1, "Sans"), (3, 1, 0x409, 2, "Bold")]);
+        name[6 + 12 + 10] = 0xff;
+        name[6 + 8 + 1] = 3;
+        let found = faces(&sfnt(&[(b"name", name)]));
+        assert_eq!(found[0].family, "S");
+        assert_eq!(found[0].style, "Regular");
+
+        // More name records than the table holds stops at the first incomplete one
+        let mut name = name_table(&[(3, 1, 0x409, 1, "Sans")]);
+        name[2] = 0xff;
+        assert_eq!(faces_family(&sfnt(&[(b"name", name)])).as_deref(), Some("Sans"));
+
+        // fvar claiming more axes than it contains keeps only the complete ones
+        let mut fvar = fvar_table(&[(b"wght", 100.0, 400.0, 900.0)]);
+        fvar[9] = 5;
+        let data = sfnt(&[(b"name", name_table(&[(3, 1, 0x409, 1, "Sans")])), (b"fvar", fvar), (b"post", vec![0; 8])]);
+        let face = &faces(&data)[0];
+        assert_eq!(face.axes.len(), 1);
+        assert!(!face.monospace);
+    }
+
+    #[test]
+    fn collections_with_bogus_counts() {
+        let font = sfnt(&[(b"name", name_table(&[(3, 1, 0x409, 1, "Sans")]))]);
+        let mut data = b"ttcf".to_vec();
+        data.extend([0, 1, 0, 0]);
+        data.extend(u32::MAX.to_be_bytes());
+        data.extend(16u32.to_be_bytes());
+        // Shift the table offsets by the collection header
+        let mut shifted = font.clone();
+        let offset = u32::from_be_bytes(shifted[20..24].try_into().unwrap()) + 16;
+        shifted[20..24].copy_from_slice(&offset.to_be_bytes());
+        data.extend(shifted);
+
+        assert_eq!(font_offsets(&data)[0], 16);
+        assert_eq!(faces(&data).len(), 1);
+        assert_eq!(faces_family(&data).as_deref(), Some("Sans"));
+        assert!(faces(b"ttcf").is_empty());
+    }
+}
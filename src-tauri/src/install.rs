@@ -1,23 +1,65 @@
 use std::fs;
-use crate::check::{has_gtk_or_wm_components, has_icons, has_cursors, has_fonts};
-use crate::extract::{extract_theme, extract_theme_info};
-use crate::utils::{install_icons, install_cursors, install_fonts, copy_dir_recursive};
-use crate::apply::apply_theme;
+use std::path::Path;
+use tauri::{AppHandle, State};
+use crate::cache::{refresh_icon_cache, refresh_font_cache};
+use crate::check::detect_components;
+use crate::conflict::{find_conflicts, unique_name, stage_renamed};
+use crate::cursors::validate_cursor_dir;
+use crate::deps::install_dependencies;
+use crate::extract::extract_theme_to;
+use crate::fonts::{find_font_files, scan_fonts};
+use crate::paths::{themes_dir, icons_dir, fonts_dir, themes_dir_for, icons_dir_for, work_dir};
+use crate::preview::remove_cached_preview;
+use crate::plasma::{install_dir, install_plasma_assets, KINDS};
+use crate::qt::{install_kvantum_themes, kvantum_dir};
+use crate::registry::{find_installed, record_user_install, remove_user_install};
+use crate::system::{install_system, uninstall_system};
+use crate::types::{ConflictResolution, InstallLocation, InstallResult, InstallScope, InstallStatus, ThemeManifest};
+use crate::jobs::JobManager;
+use crate::progress::Progress;
+use crate::utils::{install_icons, install_cursors, install_fonts, copy_dir_with_progress, dir_size};
+use crate::apply::apply_components;
+use crate::desktop::summarize;
+use crate::flatpak::grant_access;
+use crate::settings::load_settings;
 
-#[tauri::command]
+// Components that install under the user's home whatever the requested scope
+const USER_ONLY_COMPONENTS: [&str; 2] = ["kde", "qt"];
+
+#[tauri::command(async)]
 #[allow(non_snake_case)]
-pub fn install_theme_from_data(file_data: Vec<u8>, file_name: String, autoApply: bool) -> Result<String, String> {
-    // Create temp directory
-    let temp_dir = format!("/tmp/reskin_install_{}", 
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-    );
+pub fn install_theme_from_data(app: AppHandle, jobs: State<'_, JobManager>, file_data: Vec<u8>, file_name: String, autoApply: bool, scope: Option<InstallScope>, resolution: Option<ConflictResolution>) -> Result<InstallResult, String> {
+    let mut progress = Progress::new(&app, &jobs, "install", &file_name);
+    let result = install_from_data(&file_data, &file_name, autoApply, scope, resolution, &mut progress);
+    progress.finish(&result);
+    result
+}
+
+// Bundles opened from the file manager already have a path, no need to send their bytes over IPC
+#[tauri::command(async)]
+#[allow(non_snake_case)]
+pub fn install_theme_from_file(app: AppHandle, jobs: State<'_, JobManager>, file_path: String, autoApply: bool, scope: Option<InstallScope>, resolution: Option<ConflictResolution>) -> Result<InstallResult, String> {
+    let mut progress = Progress::new(&app, &jobs, "install", &file_path);
+    let result = fs::read(&file_path)
+        .map_err(|e| format!("Failed to read {}: {}", file_path, e))
+        .and_then(|file_data| {
+            let file_name = Path::new(&file_path).file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "theme.reskin".to_string());
+            install_from_data(&file_data, &file_name, autoApply, scope, resolution, &mut progress)
+        });
+    progress.finish(&result);
+    result
+}
+
+pub fn install_from_data(file_data: &[u8], file_name: &str, auto_apply: bool, scope: Option<InstallScope>, resolution: Option<ConflictResolution>, progress: &mut Progress) -> Result<InstallResult, String> {
+    // Create temp directory, dependencies installed from inside this install get their own
+    let temp_dir = work_dir("install")?;
     
-    // Save file to temp location
-    let temp_file_path = format!("{}/{}", temp_dir, file_name);
+    // Save file to temp location, keeping only the last component of the name the UI sent
+    let temp_file_path = temp_dir.join(Path::new(file_name).file_name().unwrap_or("theme.reskin".as_ref()));
     
+    progress.track_cleanup(temp_dir.clone());
     if let Err(e) = fs::create_dir_all(&temp_dir) {
         return Err(format!("Failed to create temp directory: {}", e));
     }
@@ -26,72 +68,146 @@ pub fn install_theme_from_data(file_data: Vec<u8>, file_name: String, autoApply:
         return Err(format!("Failed to write temp file: {}", e));
     }
     
-    // First extract the theme to get the theme name
-    extract_theme(temp_file_path.clone())?;
-    
-    // Get theme info to find the theme name
-    let theme_info = extract_theme_info(fs::read(&temp_file_path)
-        .map_err(|e| format!("Failed to read temp file: {}", e))?)?;
+    // Extract the theme into a staging directory next to the temp file, then install from there
+    let staging_root = temp_dir.join("staging");
+    let result = extract_theme_to(&temp_file_path.to_string_lossy(), &staging_root, progress)
+        .and_then(|(_, staging_path)| {
+            install_from_dir(staging_path.to_string_lossy().to_string(), auto_apply, scope, resolution, progress)
+        });
     
-    // Now install using the theme name
-    let result = install_theme(theme_info.name.clone(), autoApply)?;
-    
-    // Clean up temp file
+    // Clean up temp file, also when extraction failed
     let _ = fs::remove_dir_all(&temp_dir);
     
-    Ok(result)
+    result
 }
 
-#[tauri::command]
+#[tauri::command(async)]
 #[allow(non_snake_case)]
-pub fn install_theme(theme_path: String, autoApply: bool) -> Result<String, String> {
-    use std::path::Path;
+pub fn install_theme(app: AppHandle, jobs: State<'_, JobManager>, theme_path: String, autoApply: bool, scope: Option<InstallScope>, resolution: Option<ConflictResolution>) -> Result<InstallResult, String> {
+    let mut progress = Progress::new(&app, &jobs, "install", &theme_path);
+    let result = install_from_dir(theme_path, autoApply, scope, resolution, &mut progress);
+    progress.finish(&result);
+    result
+}
 
+fn install_from_dir(theme_path: String, auto_apply: bool, scope: Option<InstallScope>, resolution: Option<ConflictResolution>, progress: &mut Progress) -> Result<InstallResult, String> {
     if !Path::new(&theme_path).exists() {
         return Err(format!("Theme not found at '{}'", theme_path));
     }
 
-    let theme_name = Path::new(&theme_path)
+    let bundle_name = Path::new(&theme_path)
         .file_name()
         .ok_or("Invalid theme path")?
         .to_string_lossy()
         .to_string();
 
-    let home_dir = std::env::var("HOME").map_err(|_| "Failed to get HOME directory".to_string())?;
-    
-    let mut installed_components = Vec::new();
-    let staging_path = Path::new(&theme_path);
-
-    // Use staging_path for all component checks and installs
-    if has_gtk_or_wm_components(&staging_path) {
-        let themes_dir = format!("{}/.themes", home_dir);
-        let dest_dir = format!("{}/{}", themes_dir, theme_name);
-        fs::create_dir_all(&themes_dir)
-            .map_err(|e| format!("Failed to create ~/.themes directory: {}", e))?;
-        if Path::new(&dest_dir).exists() {
-            fs::remove_dir_all(&dest_dir)
-                .map_err(|e| format!("Failed to remove existing theme: {}", e))?;
+    // Use the source directory for all component checks
+    let components = detect_components(Path::new(&theme_path));
+
+    // Reinstalling a theme reskin manages is an upgrade, anything else needs a decision
+    let conflicts = find_conflicts(&bundle_name, &components, scope.unwrap_or_default());
+    let foreign = conflicts.iter().any(|c| !c.managed);
+    let resolution = match resolution {
+        Some(resolution) => resolution,
+        None if foreign => {
+            return Ok(InstallResult {
+                status: InstallStatus::Conflict,
+                message: format!("A theme named '{}' already exists", bundle_name),
+                theme_name: bundle_name,
+                conflicts,
+            });
         }
-        copy_dir_recursive(&theme_path, &dest_dir)
-            .map_err(|e| format!("Failed to install theme: {}", e))?;
-        installed_components.push("GTK/Window Manager theme");
+        None => ConflictResolution::Replace,
+    };
+
+    if resolution == ConflictResolution::Abort && !conflicts.is_empty() {
+        return Ok(InstallResult {
+            status: InstallStatus::Aborted,
+            message: format!("Installation of '{}' aborted", bundle_name),
+            theme_name: bundle_name,
+            conflicts,
+        });
     }
 
-    if has_icons(&staging_path) {
-        install_icons(&theme_path, &theme_name, &home_dir)?;
-        installed_components.push("Icons");
+    // The helper refuses to overwrite system themes it didn't install, say so before asking for a password
+    if resolution == ConflictResolution::Replace {
+        if let Some(conflict) = conflicts.iter().find(|c| !c.replaceable) {
+            return Err(format!("{} wasn't installed by reskin and can't be replaced for all users, rename the theme or install it for the current user", conflict.path));
+        }
     }
 
-    if has_cursors(&staging_path) {
-        install_cursors(&theme_path, &theme_name, &home_dir)?;
-        installed_components.push("Cursors");
+    // Dependencies go in first, so a failure there leaves the theme itself untouched
+    let manifest: Option<ThemeManifest> = fs::read_to_string(Path::new(&theme_path).join("reskin.json"))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok());
+    let dependencies = match &manifest {
+        Some(manifest) => install_dependencies(manifest, scope.unwrap_or_default(), progress)?,
+        None => Vec::new(),
+    };
+
+    // Renaming installs a copy with a suffixed name and rewritten index.theme
+    let mut rename_temp = None;
+    let (theme_path, theme_name) = if resolution == ConflictResolution::Rename && !conflicts.is_empty() {
+        let new_name = unique_name(&bundle_name, &components);
+        let (temp_root, staged) = stage_renamed(Path::new(&theme_path), &bundle_name, &new_name)?;
+        rename_temp = Some(temp_root);
+        (staged.to_string_lossy().to_string(), new_name)
+    } else {
+        (theme_path, bundle_name)
+    };
+
+    let result = install_components(&theme_path, &theme_name, &components, auto_apply, scope.unwrap_or_default(), progress);
+
+    if let Some(temp_root) = rename_temp {
+        let _ = fs::remove_dir_all(temp_root);
     }
 
-    if has_fonts(&staging_path) {
-        install_fonts(&theme_path, &theme_name, &home_dir)?;
-        installed_components.push("Fonts");
+    let mut message = result?;
+    if !dependencies.is_empty() {
+        message.push_str("\nDependencies installed: ");
+        message.push_str(&dependencies.join(", "));
     }
 
+    Ok(InstallResult {
+        status: InstallStatus::Installed,
+        message,
+        theme_name,
+        conflicts,
+    })
+}
+
+fn install_components(theme_path: &str, theme_name: &str, components: &[&str], auto_apply: bool, scope: InstallScope, progress: &mut Progress) -> Result<String, String> {
+    let staging_path = Path::new(theme_path);
+
+    // The helper only installs into /usr/share themes, icons and fonts, Plasma and Kvantum themes are per-user
+    let skipped: Vec<&str> = match scope {
+        InstallScope::System => components.iter().copied().filter(|c| USER_ONLY_COMPONENTS.contains(c)).collect(),
+        InstallScope::User => Vec::new(),
+    };
+    let components: Vec<&str> = components.iter().copied().filter(|c| !skipped.contains(c)).collect();
+    let components = components.as_slice();
+
+    // Look for font collisions before our own copies are known to fontconfig
+    let font_collisions = if components.contains(&"fonts") {
+        scan_fonts(staging_path).map(|scan| scan.collisions).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    let cache_reports = match scope {
+        InstallScope::User => install_user_components(theme_path, theme_name, components, progress)?,
+        InstallScope::System if !components.is_empty() => {
+            // The helper can't report progress, so this phase has no totals
+            progress.phase("installing system-wide", 0, 0);
+            // The helper reports its cache refreshes after the first line
+            let output = install_system(staging_path, theme_name, components)?;
+            output.lines().skip(1).map(|line| line.to_string()).collect()
+        }
+        InstallScope::System => Vec::new(),
+    };
+
+    let installed_components: Vec<&str> = components.iter().map(|c| component_label(c)).collect();
+
     let components_str = if installed_components.is_empty() {
         "No compatible components found".to_string()
     } else {
@@ -99,12 +215,51 @@ pub fn install_theme(theme_path: String, autoApply: bool) -> Result<String, Stri
     };
 
     let mut result_message = format!(
-    "Theme '{}' installed successfully!\nComponents: {}",
-    theme_name, components_str
+    "Theme '{}' installed successfully{}!\nComponents: {}",
+    theme_name, if scope == InstallScope::System { " for all users" } else { "" }, components_str
     );
 
-    if autoApply {
-        match apply_theme(theme_name.clone()) {
+    if !cache_reports.is_empty() {
+        result_message.push('\n');
+        result_message.push_str(&cache_reports.join("\n"));
+    }
+
+    for component in skipped {
+        result_message.push_str(&format!("\n⚠️ {} can only be installed for the current user", component_label(component)));
+    }
+
+    // libadwaita apps only follow themes that ship gtk-4.0/gtk.css
+    if components.contains(&"theme") && !staging_path.join("gtk-4.0/gtk.css").is_file() {
+        result_message.push_str("\n⚠️ No gtk-4.0 directory, libadwaita apps won't use this theme");
+    }
+
+    if components.contains(&"cursors") {
+        let report = validate_cursor_dir(staging_path);
+        if !report.missing.is_empty() {
+            result_message.push_str("\n⚠️ Cursor theme is missing: ");
+            result_message.push_str(&report.missing.join(", "));
+        }
+    }
+
+    if !font_collisions.is_empty() {
+        let names: Vec<String> = font_collisions.iter()
+            .map(|c| format!("{} {} ({})", c.family, c.style, c.installed_path))
+            .collect();
+        result_message.push_str("\n⚠️ Fonts already installed elsewhere: ");
+        result_message.push_str(&names.join(", "));
+    }
+
+    // Auto-apply writes the full Flatpak overrides, otherwise only grant access
+    if load_settings().flatpak_integration && !auto_apply {
+        if let Err(e) = grant_access(scope == InstallScope::System) {
+            result_message.push_str("\n\n⚠️ Failed to update Flatpak overrides: ");
+            result_message.push_str(&e);
+        }
+    }
+
+    if auto_apply {
+        progress.phase("applying", 0, 0);
+        match summarize(&apply_components(theme_name, None, true), "Failed to apply any theme components") {
             Ok(apply_msg) => {
                 result_message.push_str("\n\n");
                 result_message.push_str(&apply_msg);
@@ -118,4 +273,149 @@ pub fn install_theme(theme_path: String, autoApply: bool) -> Result<String, Stri
 
     Ok(result_message)
 
-}
\ No newline at end of file
+}
+
+fn cache_report(result: Result<String, String>, cache: &str) -> String {
+    match result {
+        Ok(message) => format!("✅ {}", message),
+        Err(e) => format!("⚠️ {} not refreshed: {}", cache, e),
+    }
+}
+
+// Install components into the user's home and record them in the registry,
+// returning a report line for each cache refresh
+fn install_user_components(theme_path: &str, theme_name: &str, components: &[&str], progress: &mut Progress) -> Result<Vec<String>, String> {
+    let mut paths = Vec::new();
+    let mut cache_reports = Vec::new();
+    let (tree_bytes, tree_files) = dir_size(Path::new(theme_path));
+
+    for component in components {
+        progress.check_cancelled()?;
+        match *component {
+            "theme" => {
+                progress.phase("installing theme", tree_bytes, tree_files);
+                let themes_dir = themes_dir()?;
+                let dest_dir = themes_dir.join(theme_name);
+                fs::create_dir_all(&themes_dir)
+                    .map_err(|e| format!("Failed to create {} directory: {}", themes_dir.display(), e))?;
+                if dest_dir.exists() {
+                    fs::remove_dir_all(&dest_dir)
+                        .map_err(|e| format!("Failed to remove existing theme: {}", e))?;
+                }
+                progress.track_cleanup(dest_dir.clone());
+                copy_dir_with_progress(theme_path, &dest_dir.to_string_lossy(), progress)
+                    .map_err(|e| format!("Failed to install theme: {}", e))?;
+                paths.push(dest_dir);
+            }
+            "icons" => {
+                progress.phase("installing icons", tree_bytes, tree_files);
+                install_icons(theme_path, theme_name, progress)?;
+                paths.push(icons_dir()?.join(theme_name));
+            }
+            "cursors" => {
+                progress.phase("installing cursors", tree_bytes, tree_files);
+                install_cursors(theme_path, theme_name, progress)?;
+                paths.push(icons_dir()?.join(theme_name));
+            }
+            "kde" => {
+                progress.phase("installing Plasma assets", tree_bytes, tree_files);
+                let owned = find_installed(theme_name, InstallScope::User).map(|t| t.paths).unwrap_or_default();
+                paths.extend(install_plasma_assets(Path::new(theme_path), &owned, progress)?);
+            }
+            "qt" => {
+                progress.phase("installing Kvantum themes", tree_bytes, tree_files);
+                let owned = find_installed(theme_name, InstallScope::User).map(|t| t.paths).unwrap_or_default();
+                paths.extend(install_kvantum_themes(Path::new(theme_path), &owned, progress)?);
+            }
+            "fonts" => {
+                let fonts = find_font_files(Path::new(theme_path));
+                let font_bytes = fonts.iter().filter_map(|f| fs::metadata(f).ok()).map(|m| m.len()).sum();
+                progress.phase("installing fonts", font_bytes, fonts.len() as u64);
+                progress.track_cleanup(fonts_dir()?.join(theme_name));
+                install_fonts(theme_path, theme_name, progress)?;
+                paths.push(fonts_dir()?.join(theme_name));
+            }
+            _ => {}
+        }
+    }
+
+    // Last chance to cancel, once the theme is recorded the cache refresh runs to the end
+    progress.check_cancelled()?;
+    paths.dedup();
+    record_user_install(theme_name, components, &paths)?;
+
+    progress.phase("refreshing caches", 0, 0);
+
+    // Icons and cursors share a directory, refresh its cache once
+    if components.contains(&"icons") || components.contains(&"cursors") {
+        let icon_dir = icons_dir()?.join(theme_name);
+        cache_reports.push(cache_report(refresh_icon_cache(&icon_dir), "Icon cache"));
+    }
+    if components.contains(&"fonts") {
+        let font_dir = fonts_dir()?.join(theme_name);
+        cache_reports.push(cache_report(refresh_font_cache(&font_dir), "Font cache"));
+    }
+
+    Ok(cache_reports)
+}
+
+fn component_label(component: &str) -> &'static str {
+    match component {
+        "theme" => "GTK/Window Manager theme",
+        "icons" => "Icons",
+        "cursors" => "Cursors",
+        "fonts" => "Fonts",
+        "kde" => "KDE Plasma assets",
+        "qt" => "Kvantum themes",
+        _ => "Unknown",
+    }
+}
+
+// Only directories directly inside one of reskin's install roots may be removed
+fn is_install_dir(path: &Path) -> bool {
+    let roots = [
+        themes_dir_for(InstallLocation::Legacy),
+        themes_dir_for(InstallLocation::Xdg),
+        icons_dir_for(InstallLocation::Legacy),
+        icons_dir_for(InstallLocation::Xdg),
+        fonts_dir(),
+    ];
+    let plasma_roots = KINDS.into_iter().map(install_dir);
+    let kvantum_root = kvantum_dir();
+
+    match path.parent() {
+        Some(parent) => roots.into_iter().chain(plasma_roots).chain([kvantum_root]).flatten().any(|root| root == parent),
+        None => false,
+    }
+}
+
+#[tauri::command]
+pub fn uninstall_theme(theme_name: String, scope: Option<InstallScope>) -> Result<String, String> {
+    remove_cached_preview(&theme_name);
+    if scope.unwrap_or_default() == InstallScope::System {
+        return uninstall_system(&theme_name);
+    }
+
+    let entry = find_installed(&theme_name, InstallScope::User)
+        .ok_or(format!("Theme '{}' was not installed by reskin", theme_name))?;
+
+    for path in &entry.paths {
+        let path = Path::new(path);
+        if !is_install_dir(path) {
+            return Err(format!("Refusing to remove '{}': not a theme install directory", path.display()));
+        }
+        // Plasma color schemes are single files
+        let removed = if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else if path.exists() {
+            fs::remove_file(path)
+        } else {
+            Ok(())
+        };
+        removed.map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+    }
+
+    remove_user_install(&theme_name)?;
+
+    Ok(format!("Theme '{}' uninstalled", theme_name))
+}
@@ -0,0 +1,159 @@
+// Job manager for long-running operations: ids, state tracking and cancellation
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tauri::State;
+use crate::types::{JobInfo, JobState, ProgressEvent};
+
+// Finished jobs kept around for the task list
+const MAX_FINISHED_JOBS: usize = 20;
+
+pub const CANCELLED: &str = "Cancelled";
+
+struct Job {
+    info: JobInfo,
+    cancelled: Arc<AtomicBool>,
+    cleanup: Vec<PathBuf>, // Temp directories and partial files, removed unless the job succeeds
+}
+
+#[derive(Default)]
+struct JobTable {
+    next_id: u64,
+    jobs: Vec<Job>,
+}
+
+// Managed as Tauri state, clones share the same table
+#[derive(Clone, Default)]
+pub struct JobManager {
+    table: Arc<Mutex<JobTable>>,
+}
+
+// What a running operation holds on to for its job
+#[derive(Clone)]
+pub struct JobHandle {
+    pub id: u64,
+    cancelled: Arc<AtomicBool>,
+    manager: JobManager,
+}
+
+impl JobManager {
+    pub fn start(&self, operation: &str, description: &str) -> JobHandle {
+        let mut table = self.table.lock().unwrap();
+        table.next_id += 1;
+        let id = table.next_id;
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        table.jobs.push(Job {
+            info: JobInfo {
+                id,
+                operation: operation.to_string(),
+                description: description.to_string(),
+                state: JobState::Running,
+                error: None,
+                progress: None,
+                started_at: SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+            },
+            cancelled: cancelled.clone(),
+            cleanup: Vec::new(),
+        });
+
+        JobHandle { id, cancelled, manager: self.clone() }
+    }
+
+    fn with_job<F: FnOnce(&mut Job)>(&self, id: u64, f: F) {
+        let mut table = self.table.lock().unwrap();
+        if let Some(job) = table.jobs.iter_mut().find(|job| job.info.id == id) {
+            f(job);
+        }
+    }
+
+    pub fn list(&self) -> Vec<JobInfo> {
+        self.table.lock().unwrap().jobs.iter().map(|job| job.info.clone()).collect()
+    }
+
+    pub fn cancel(&self, id: u64) -> Result<(), String> {
+        let table = self.table.lock().unwrap();
+        let job = table.jobs.iter()
+            .find(|job| job.info.id == id)
+            .ok_or(format!("No job with id {}", id))?;
+        if job.info.state != JobState::Running {
+            return Err(format!("Job {} is not running", id));
+        }
+        job.cancelled.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+impl JobHandle {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    // Remove this path if the job doesn't complete
+    pub fn track_cleanup(&self, path: PathBuf) {
+        self.manager.with_job(self.id, |job| job.cleanup.push(path));
+    }
+
+    pub fn cleanup_len(&self) -> usize {
+        let table = self.manager.table.lock().unwrap();
+        table.jobs.iter().find(|job| job.info.id == self.id).map_or(0, |job| job.cleanup.len())
+    }
+
+    // Keep everything tracked after the first `len` paths, even if the job fails later
+    pub fn keep_cleanup(&self, len: usize) {
+        self.manager.with_job(self.id, |job| job.cleanup.truncate(len));
+    }
+
+    pub fn update_progress(&self, event: &ProgressEvent) {
+        self.manager.with_job(self.id, |job| job.info.progress = Some(event.clone()));
+    }
+
+    // Record the outcome, cleaning up after failed or cancelled jobs
+    pub fn finish(&self, error: Option<&str>) {
+        let mut table = self.manager.table.lock().unwrap();
+        if let Some(job) = table.jobs.iter_mut().find(|job| job.info.id == self.id) {
+            job.info.state = match error {
+                None => JobState::Completed,
+                Some(_) if self.is_cancelled() => JobState::Cancelled,
+                Some(_) => JobState::Failed,
+            };
+            job.info.error = error.map(|e| e.to_string());
+
+            if error.is_some() {
+                for path in job.cleanup.drain(..) {
+                    if path.is_dir() {
+                        let _ = fs::remove_dir_all(&path);
+                    } else {
+                        let _ = fs::remove_file(&path);
+                    }
+                }
+            }
+        }
+
+        // Drop the oldest finished jobs beyond the limit
+        let finished = table.jobs.iter().filter(|job| job.info.state != JobState::Running).count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+        table.jobs.retain(|job| {
+            if excess > 0 && job.info.state != JobState::Running {
+                excess -= 1;
+                return false;
+            }
+            true
+        });
+    }
+}
+
+#[tauri::command]
+pub fn list_jobs(jobs: State<'_, JobManager>) -> Result<Vec<JobInfo>, String> {
+    Ok(jobs.list())
+}
+
+#[tauri::command]
+pub fn cancel_job(jobs: State<'_, JobManager>, id: u64) -> Result<(), String> {
+    jobs.cancel(id)
+}
@@ -0,0 +1,77 @@
+// Opening .reskin files and reskin:// links passed on the command line or handed over by a second instance
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::deeplink::{percent_decode, PendingLink, SCHEME};
+use crate::extract::extract_theme_info_from_file;
+use crate::types::OpenedTheme;
+
+// Held until the frontend asks for it, since it may not be listening yet at startup
+#[derive(Default)]
+pub struct PendingOpen(pub Mutex<Option<OpenedTheme>>);
+
+// Bundles are recognised by their RSKN magic, not just the extension
+pub fn is_bundle(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|_| &magic == b"RSKN")
+}
+
+// Bundle paths among the arguments, relative ones resolved against the caller's directory
+pub fn bundle_paths_from_args(args: &[String], cwd: &Path) -> Vec<PathBuf> {
+    args.iter()
+        .skip(1) // Executable
+        .filter(|arg| !arg.starts_with('-') && !arg.starts_with(SCHEME))
+        // The desktop entry uses %U, so file managers may pass file:// URIs
+        .map(|arg| match arg.strip_prefix("file://") {
+            Some(path) => PathBuf::from(percent_decode(path)),
+            None => PathBuf::from(arg),
+        })
+        .map(|path| cwd.join(path))
+        .filter(|path| is_bundle(path))
+        .collect()
+}
+
+// Queue the first link or bundle in argv for the installer and tell the frontend about it
+pub fn open_from_args(app: &AppHandle, args: &[String], cwd: &str) {
+    if let Some(link) = args.iter().skip(1).find(|arg| arg.starts_with(SCHEME)) {
+        if let Some(pending) = app.try_state::<PendingLink>() {
+            *pending.0.lock().unwrap() = Some(link.clone());
+        }
+        let _ = app.emit("open-link", ());
+        focus_main_window(app);
+        return;
+    }
+
+    let path = match bundle_paths_from_args(args, Path::new(cwd)).into_iter().next() {
+        Some(path) => path,
+        None => return,
+    };
+    let path = path.to_string_lossy().to_string();
+
+    let opened = match extract_theme_info_from_file(path.clone()) {
+        Ok(manifest) => OpenedTheme { path, manifest: Some(manifest), error: None },
+        Err(e) => OpenedTheme { path, manifest: None, error: Some(e) },
+    };
+
+    if let Some(pending) = app.try_state::<PendingOpen>() {
+        *pending.0.lock().unwrap() = Some(opened);
+    }
+    let _ = app.emit("open-theme", ());
+    focus_main_window(app);
+}
+
+fn focus_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+#[tauri::command]
+pub fn take_pending_open(pending: State<'_, PendingOpen>) -> Result<Option<OpenedTheme>, String> {
+    Ok(pending.0.lock().unwrap().take())
+}
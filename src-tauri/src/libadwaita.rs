@@ -0,0 +1,225 @@
+// libadwaita theming: GTK4 apps ignore gtk-theme and only load ~/.config/gtk-4.0/gtk.css
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use crate::paths::{config_home, find_theme_dir, reskin_config_dir};
+use crate::settings::{load_settings, save_settings};
+
+// What a theme's gtk-4.0 directory provides for libadwaita
+const LINKED_FILES: [&str; 3] = ["gtk.css", "gtk-dark.css", "assets"];
+
+// Snapshot key for the linked theme
+pub const LIBADWAITA: &str = "libadwaita";
+
+// What reskin placed in ~/.config/gtk-4.0, and which of the user's own files it moved aside
+#[derive(Serialize, Deserialize, Default)]
+struct LibadwaitaState {
+    theme: Option<String>,
+    // The gtk-4.0 directory the links point into
+    #[serde(default)]
+    source: Option<PathBuf>,
+    linked: Vec<String>,
+    backed_up: Vec<String>,
+}
+
+fn state_file() -> Result<PathBuf, String> {
+    Ok(reskin_config_dir()?.join("libadwaita.json"))
+}
+
+fn backup_dir() -> Result<PathBuf, String> {
+    Ok(reskin_config_dir()?.join("gtk-4.0-backup"))
+}
+
+fn gtk4_config_dir() -> Result<PathBuf, String> {
+    Ok(config_home()?.join("gtk-4.0"))
+}
+
+fn load_state() -> LibadwaitaState {
+    state_file()
+        .ok()
+        .and_then(|file| fs::read_to_string(file).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(state: &LibadwaitaState) -> Result<(), String> {
+    let config_dir = reskin_config_dir()?;
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    let json = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize libadwaita state: {}", e))?;
+    fs::write(state_file()?, json)
+        .map_err(|e| format!("Failed to write libadwaita state: {}", e))
+}
+
+// Only links that still point at the theme are removed, anything else was put there since
+fn remove_links(config_dir: &Path, source: Option<&Path>, names: &[String]) -> Result<(), String> {
+    for name in names {
+        let link = config_dir.join(name);
+        let ours = fs::read_link(&link)
+            .is_ok_and(|target| source.is_some_and(|source| target == source.join(name)));
+        if ours {
+            fs::remove_file(&link)
+                .map_err(|e| format!("Failed to remove {}: {}", link.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+// Remove reskin's links, keeping the user's backups where they are
+fn unlink(state: &mut LibadwaitaState) -> Result<(), String> {
+    // States written before the source was recorded only have the theme name
+    let source = state.source.take().or_else(|| {
+        state.theme.as_deref().and_then(find_theme_dir).map(|dir| dir.join("gtk-4.0"))
+    });
+    remove_links(&gtk4_config_dir()?, source.as_deref(), &state.linked)?;
+    state.linked.clear();
+    state.theme = None;
+    Ok(())
+}
+
+// The gtk-4.0 directory of a theme, if it has one
+pub fn theme_gtk4_dir(theme_name: &str) -> Option<PathBuf> {
+    find_theme_dir(theme_name)
+        .map(|dir| dir.join("gtk-4.0"))
+        .filter(|dir| dir.join("gtk.css").is_file())
+}
+
+fn link_files(source: &Path, state: &mut LibadwaitaState) -> Result<(), String> {
+    let config_dir = gtk4_config_dir()?;
+    let backup_dir = backup_dir()?;
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create {}: {}", config_dir.display(), e))?;
+
+    for name in LINKED_FILES {
+        let target = source.join(name);
+        if !target.exists() {
+            continue;
+        }
+        let link = config_dir.join(name);
+
+        // Anything still here is the user's own, move it aside once
+        if fs::symlink_metadata(&link).is_ok() {
+            if state.backed_up.iter().any(|b| b == name) {
+                return Err(format!("{} changed since the last backup, revert libadwaita theming first", link.display()));
+            }
+            fs::create_dir_all(&backup_dir)
+                .map_err(|e| format!("Failed to create {}: {}", backup_dir.display(), e))?;
+            fs::rename(&link, backup_dir.join(name))
+                .map_err(|e| format!("Failed to back up {}: {}", link.display(), e))?;
+            state.backed_up.push(name.to_string());
+        }
+
+        symlink(&target, &link)
+            .map_err(|e| format!("Failed to link {}: {}", link.display(), e))?;
+        state.linked.push(name.to_string());
+    }
+
+    Ok(())
+}
+
+// Link the theme's gtk-4.0 files into ~/.config/gtk-4.0, backing up what the user had there
+pub fn apply_libadwaita(theme_name: &str) -> Result<(), String> {
+    let mut state = load_state();
+    let source = match theme_gtk4_dir(theme_name) {
+        Some(source) => source,
+        None => {
+            // Don't leave the previous theme's CSS behind
+            unlink(&mut state)?;
+            save_state(&state)?;
+            return Err(format!("{} has no gtk-4.0 directory, libadwaita apps keep the default style", theme_name));
+        }
+    };
+
+    unlink(&mut state)?;
+    // Record partial progress too, so a later revert knows what is ours
+    state.source = Some(source.clone());
+    let result = link_files(&source, &mut state);
+    if result.is_ok() {
+        state.theme = Some(theme_name.to_string());
+    }
+    save_state(&state)?;
+    result
+}
+
+// Remove the links and put the user's original files back
+pub fn revert_libadwaita() -> Result<String, String> {
+    let mut state = load_state();
+    unlink(&mut state)?;
+
+    let config_dir = gtk4_config_dir()?;
+    let backup_dir = backup_dir()?;
+    // A backup whose place was taken since stays in the backup directory
+    let mut kept = Vec::new();
+    for name in state.backed_up.drain(..) {
+        let backup = backup_dir.join(&name);
+        if fs::symlink_metadata(config_dir.join(&name)).is_ok() {
+            kept.push(name);
+        } else if fs::symlink_metadata(&backup).is_ok() {
+            fs::rename(&backup, config_dir.join(&name))
+                .map_err(|e| format!("Failed to restore {}: {}", name, e))?;
+        }
+    }
+    state.backed_up = kept;
+    let _ = fs::remove_dir(&backup_dir);
+
+    save_state(&state)?;
+    Ok("libadwaita theming reverted".to_string())
+}
+
+// Theme currently linked for libadwaita apps
+pub fn linked_theme() -> Option<String> {
+    load_state().theme
+}
+
+#[tauri::command]
+pub fn get_libadwaita_theming() -> Result<bool, String> {
+    Ok(load_settings().libadwaita_theming)
+}
+
+#[tauri::command]
+pub fn set_libadwaita_theming(enabled: bool) -> Result<(), String> {
+    let mut settings = load_settings();
+    settings.libadwaita_theming = enabled;
+    save_settings(&settings)?;
+
+    // Turning it off puts the user's own gtk-4.0 files back
+    if !enabled {
+        revert_libadwaita()?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn revert_libadwaita_theming() -> Result<String, String> {
+    revert_libadwaita()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_only_links_into_the_theme() {
+        let dir = std::env::temp_dir().join(format!("reskin-libadwaita-{}", std::process::id()));
+        let source = dir.join("Nordic/gtk-4.0");
+        let other = dir.join("Other/gtk-4.0");
+        let config_dir = dir.join("config");
+        for d in [&source, &other, &config_dir] {
+            fs::create_dir_all(d).unwrap();
+        }
+        symlink(source.join("gtk.css"), config_dir.join("gtk.css")).unwrap();
+        symlink(other.join("gtk-dark.css"), config_dir.join("gtk-dark.css")).unwrap();
+        fs::create_dir(config_dir.join("assets")).unwrap();
+
+        let names = LINKED_FILES.map(String::from);
+        let result = remove_links(&config_dir, Some(&source), &names);
+        let remaining = LINKED_FILES.map(|name| fs::symlink_metadata(config_dir.join(name)).is_ok());
+        let untouched = remove_links(&config_dir, None, &names);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(result.is_ok() && untouched.is_ok());
+        assert_eq!(remaining, [false, true, true]);
+    }
+}
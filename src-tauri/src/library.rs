@@ -0,0 +1,109 @@
+// Scan every theme on the system, including ones reskin didn't install
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::desktop::{detect_backend, CURSOR, GTK, ICONS, SHELL};
+use crate::check::{has_gtk_or_wm_components, has_shell_theme, is_icon_theme};
+use crate::paths::{data_home, home_dir};
+use crate::preview::existing_preview;
+use crate::registry::load_registry;
+use crate::types::{InstallScope, SystemTheme};
+
+// Name= from the theme's index.theme
+fn index_theme_name(theme_path: &Path) -> Option<String> {
+    fs::read_to_string(theme_path.join("index.theme")).ok()?
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Name="))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+// (directory, holds icon themes, scope) in lookup order
+fn scan_roots() -> Vec<(PathBuf, bool, InstallScope)> {
+    let mut roots = Vec::new();
+    if let Ok(home) = home_dir() {
+        roots.push((home.join(".themes"), false, InstallScope::User));
+    }
+    if let Ok(data) = data_home() {
+        roots.push((data.join("themes"), false, InstallScope::User));
+    }
+    if let Ok(home) = home_dir() {
+        roots.push((home.join(".icons"), true, InstallScope::User));
+    }
+    if let Ok(data) = data_home() {
+        roots.push((data.join("icons"), true, InstallScope::User));
+    }
+    roots.push((PathBuf::from("/usr/share/themes"), false, InstallScope::System));
+    roots.push((PathBuf::from("/usr/share/icons"), true, InstallScope::System));
+    roots
+}
+
+#[tauri::command(async)]
+pub fn scan_system_themes() -> Result<Vec<SystemTheme>, String> {
+    let managed_paths: Vec<String> = [InstallScope::User, InstallScope::System].into_iter()
+        .flat_map(load_registry)
+        .flat_map(|theme| theme.paths)
+        .collect();
+    let current = detect_backend().snapshot();
+
+    let mut themes = Vec::new();
+    for (root, icon_root, scope) in scan_roots() {
+        let mut entries: Vec<PathBuf> = match fs::read_dir(&root) {
+            Ok(entries) => entries.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()).collect(),
+            Err(_) => continue,
+        };
+        entries.sort();
+
+        for path in entries {
+            // Cheap checks only, /usr/share/icons holds hundreds of large trees and the full
+            // detection would parse every cursor and walk every directory for fonts
+            let mut components = Vec::new();
+            if icon_root {
+                if is_icon_theme(&path) {
+                    components.push("icons");
+                }
+                if path.join("cursors").is_dir() {
+                    components.push("cursors");
+                }
+            } else {
+                if has_gtk_or_wm_components(&path) {
+                    components.push("theme");
+                }
+                if has_shell_theme(&path) {
+                    components.push("shell");
+                }
+            }
+            if components.is_empty() {
+                continue;
+            }
+
+            let id = path.file_name().unwrap().to_string_lossy().to_string();
+            let path_str = path.to_string_lossy().to_string();
+            let applied = components.iter()
+                .filter(|component| {
+                    let key = match **component {
+                        "theme" => GTK,
+                        "shell" => SHELL,
+                        "icons" => ICONS,
+                        _ => CURSOR,
+                    };
+                    current.values.get(key) == Some(&id)
+                })
+                .map(|component| component.to_string())
+                .collect();
+
+            themes.push(SystemTheme {
+                name: index_theme_name(&path).unwrap_or(id.clone()),
+                managed: managed_paths.contains(&path_str),
+                components: components.iter().map(|c| c.to_string()).collect(),
+                id,
+                path: path_str,
+                scope,
+                applied,
+                // Only what's on disk already, rendering every theme here would be far too slow
+                preview: existing_preview(&path).map(|p| p.to_string_lossy().to_string()),
+            });
+        }
+    }
+
+    Ok(themes)
+}
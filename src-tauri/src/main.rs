@@ -1,9 +1,23 @@
-mod info; mod file; mod extract; mod check; mod bundle; mod apply; mod recent; mod types; mod utils; mod install; mod marketplace; mod window;
+mod info; mod file; mod extract; mod check; mod bundle; mod apply; mod recent; mod types; mod utils; mod install; mod marketplace; mod window; mod paths; mod settings; mod registry; mod system; mod flatpak; mod cache; mod fonts; mod cursors; mod conflict; mod progress; mod jobs; mod launch; mod deeplink; mod deps; mod library; mod palette; mod preview; mod desktop; mod snapshots; mod libadwaita; mod schedule; mod plasma; mod qt;
 use tauri::{Manager};
 
 fn main() {
+	// The light/dark scheduler runs without a window, and before single-instance would hand it to the app
+	if std::env::args().any(|arg| arg == schedule::SCHEDULE_ARG) {
+		schedule::run_scheduler();
+		return;
+	}
+
 	tauri::Builder::default()
+		// Must be registered first, a second launch hands its arguments over and exits
+		.plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+			launch::open_from_args(app, &argv, &cwd);
+		}))
 		.plugin(tauri_plugin_opener::init())
+		.manage(jobs::JobManager::default())
+		.manage(launch::PendingOpen::default())
+		.manage(deeplink::PendingLink::default())
+		.manage(deeplink::LinkDownload::default())
 		.invoke_handler(tauri::generate_handler![
 			info::get_app_version, info::init,
 			file::select_folder, file::select_file,
@@ -11,12 +25,30 @@ fn main() {
 			bundle::bundle_theme, bundle::bundle_theme_from_directory,
 			apply::apply_theme,
 			recent::get_recent_themes, recent::add_recent_theme,
-			install::install_theme_from_data, install::install_theme,
+			install::install_theme_from_data, install::install_theme_from_file, install::install_theme, install::uninstall_theme, conflict::check_install_conflicts,
+			registry::get_installed_themes, library::scan_system_themes,
 			marketplace::fetch_marketplace_themes, marketplace::get_theme_info, marketplace::download_theme,
 			window::minimize, window::toggle_maximize, window::close,
-			utils::apply_config_file, utils::backup_config_file
+			utils::apply_config_file, utils::backup_config_file,
+			settings::get_install_location, settings::set_install_location,
+			fonts::discover_fonts, cursors::validate_cursors, cursors::set_cursor_theme,
+			flatpak::get_flatpak_integration, flatpak::set_flatpak_integration, flatpak::revert_flatpak_overrides,
+			libadwaita::get_libadwaita_theming, libadwaita::set_libadwaita_theming, libadwaita::revert_libadwaita_theming,
+			qt::get_qt_color_schemes, qt::set_qt_color_schemes, qt::get_qt_environment, qt::set_qt_environment,
+			jobs::list_jobs, jobs::cancel_job,
+			launch::take_pending_open,
+			deeplink::take_pending_link, deeplink::preview_deep_link, deeplink::confirm_deep_link,
+			deps::resolve_dependencies,
+			palette::extract_palette, preview::generate_preview, preview::get_theme_preview, desktop::detect_desktop,
+			snapshots::list_snapshots, snapshots::restore_snapshot, snapshots::undo_last_apply,
+			schedule::get_schedule, schedule::configure_schedule, schedule::get_schedule_status
 		])
 		.setup(|_app| {
+			// Files opened from the file manager and reskin:// links arrive as arguments
+			let args: Vec<String> = std::env::args().collect();
+			let cwd = std::env::current_dir().unwrap_or_default();
+			launch::open_from_args(_app.handle(), &args, &cwd.to_string_lossy());
+
 			// Code to run only on debug versions
 			#[cfg(debug_assertions)] {
 				if let Some(window) = _app.get_webview_window("main") {
@@ -1,5 +1,10 @@
 use std::fs;
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+use crate::extract::is_plain_name;
+use crate::jobs::JobManager;
+use crate::progress::Progress;
 use reqwest::Client;
 use dotenv::dotenv;
 use std::env;
@@ -65,7 +70,18 @@ pub async fn fetch_marketplace_themes(databaseId: &str, collectionId: &str) -> R
 
 #[tauri::command]
 #[allow(non_snake_case)]
-pub async fn download_theme(themeFileId: String, themeName: String) -> Result<(), String> {
+pub async fn download_theme(app: AppHandle, jobs: State<'_, JobManager>, themeFileId: String, themeName: String) -> Result<(), String> {
+    let mut progress = Progress::new(&app, &jobs, "download", &themeName);
+    let result = download_theme_file(&themeFileId, &themeName, &mut progress).await;
+    progress.finish(&result);
+    result
+}
+
+#[allow(non_snake_case)]
+async fn download_theme_file(themeFileId: &str, themeName: &str, progress: &mut Progress) -> Result<(), String> {
+    if !is_plain_name(themeName) {
+        return Err(format!("Invalid theme name: {}", themeName));
+    }
     dotenv().ok();
     // Import Appwrite credientials from .env
     let endpoint = env::var("VITE_APPWRITE_ENDPOINT").map_err(|_| "APPWRITE_ENDPOINT not set".to_string())?;
@@ -84,15 +100,25 @@ pub async fn download_theme(themeFileId: String, themeName: String) -> Result<()
         .await
         .map_err(|e| format!("Failed to download theme: {}", e))?;
     if response.status().is_success() {
-        let bytes = response.bytes().await.map_err(|e| format!("Failed to read theme bytes: {}", e))?;
-        
         let home_dir = env::home_dir().ok_or("Failed to get home directory".to_string())?;
         let reskin_dir = Path::new(&home_dir).join(".reskin-themes");
         let theme_path = reskin_dir.join(format!("{}.reskin", themeName));
 
         fs::create_dir_all(&reskin_dir).map_err(|e| format!("Failed to create directory: {}", e))?;
 
-        fs::write(&theme_path, &bytes).map_err(|e| format!("Failed to save theme file: {}", e))?;
+        // Stream to disk chunk by chunk so large themes can report progress
+        progress.track_cleanup(theme_path.clone());
+        let mut file = fs::File::create(&theme_path).map_err(|e| format!("Failed to save theme file: {}", e))?;
+        progress.phase("downloading", response.content_length().unwrap_or(0), 1);
+        progress.set_file(&format!("{}.reskin", themeName));
+
+        let mut response = response;
+        while let Some(chunk) = response.chunk().await.map_err(|e| format!("Failed to read theme bytes: {}", e))? {
+            progress.check_cancelled()?;
+            file.write_all(&chunk).map_err(|e| format!("Failed to save theme file: {}", e))?;
+            progress.add(chunk.len() as u64, 0);
+        }
+        progress.add(0, 1);
         
         Ok(())
     } else {
@@ -100,4 +126,73 @@ pub async fn download_theme(themeFileId: String, themeName: String) -> Result<()
         let error_text = response.text().await.unwrap_or_default();
         Err(format!("Failed to download theme: status {} - {}", status_code, error_text))
     }
-}
\ No newline at end of file
+}
+// Links can point anywhere, so don't buffer arbitrarily large responses
+const MAX_BUNDLE_SIZE: usize = 512 * 1024 * 1024;
+
+// Download a marketplace theme file into memory, so it can be previewed before anything is saved
+#[allow(non_snake_case)]
+pub async fn fetch_theme_file(themeFileId: &str, progress: &mut Progress) -> Result<Vec<u8>, String> {
+    dotenv().ok();
+    let endpoint = env::var("VITE_APPWRITE_ENDPOINT").map_err(|_| "APPWRITE_ENDPOINT not set".to_string())?;
+    let projectId = env::var("VITE_APPWRITE_PROJECT_ID").map_err(|_| "APPWRITE_PROJECT_ID not set".to_string())?;
+    let apiKey = env::var("VITE_APPWRITE_API_KEY").map_err(|_| "APPWRITE_API_KEY not set".to_string())?;
+
+    let client = Client::new();
+    let url = format!("{}/storage/buckets/{}/files/{}/download", endpoint, "themes", themeFileId);
+    let response = client
+        .get(&url)
+        .header("X-Appwrite-Project", projectId)
+        .header("X-Appwrite-Key", apiKey)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download theme: {}", e))?;
+    read_bundle_response(response, progress).await
+}
+
+// Download a bundle from a plain URL into memory
+pub async fn fetch_bundle_url(url: &str, progress: &mut Progress) -> Result<Vec<u8>, String> {
+    let response = Client::new()
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download theme: {}", e))?;
+    read_bundle_response(response, progress).await
+}
+
+async fn read_bundle_response(mut response: reqwest::Response, progress: &mut Progress) -> Result<Vec<u8>, String> {
+    if !response.status().is_success() {
+        let status_code = response.status().as_u16();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to download theme: status {} - {}", status_code, error_text));
+    }
+
+    progress.phase("downloading", response.content_length().unwrap_or(0), 1);
+    let mut data = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Failed to read theme bytes: {}", e))? {
+        progress.check_cancelled()?;
+        if data.len() + chunk.len() > MAX_BUNDLE_SIZE {
+            return Err("Theme file is too large".to_string());
+        }
+        data.extend_from_slice(&chunk);
+        progress.add(chunk.len() as u64, 0);
+    }
+    progress.add(0, 1);
+
+    Ok(data)
+}
+
+// Save a downloaded bundle where download_theme would have put it
+pub fn save_downloaded_theme(theme_name: &str, data: &[u8]) -> Result<PathBuf, String> {
+    // The name comes from a remote manifest
+    if !is_plain_name(theme_name) {
+        return Err(format!("Invalid theme name: {}", theme_name));
+    }
+    let home_dir = env::home_dir().ok_or("Failed to get home directory".to_string())?;
+    let reskin_dir = Path::new(&home_dir).join(".reskin-themes");
+    let theme_path = reskin_dir.join(format!("{}.reskin", theme_name));
+
+    fs::create_dir_all(&reskin_dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+    fs::write(&theme_path, data).map_err(|e| format!("Failed to save theme file: {}", e))?;
+    Ok(theme_path)
+}
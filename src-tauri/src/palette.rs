@@ -0,0 +1,473 @@
+// Color palette of a GTK theme, read from @define-color declarations in its stylesheets
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::types::{PaletteColor, PaletteVariant, ThemePalette};
+
+// libadwaita names and the GTK 3 names themes used before them
+const NAMED_COLORS: [(&str, &[&str]); 18] = [
+    ("accent_color", &["accent_bg_color", "theme_selected_bg_color", "selected_bg_color"]),
+    ("accent_bg_color", &["theme_selected_bg_color", "selected_bg_color"]),
+    ("accent_fg_color", &["theme_selected_fg_color", "selected_fg_color"]),
+    ("window_bg_color", &["theme_bg_color", "bg_color"]),
+    ("window_fg_color", &["theme_fg_color", "fg_color"]),
+    ("view_bg_color", &["theme_base_color", "base_color"]),
+    ("view_fg_color", &["theme_text_color", "text_color"]),
+    ("headerbar_bg_color", &["window_bg_color", "theme_bg_color", "bg_color"]),
+    ("headerbar_fg_color", &["window_fg_color", "theme_fg_color", "fg_color"]),
+    ("card_bg_color", &["view_bg_color", "theme_base_color", "base_color"]),
+    ("card_fg_color", &["view_fg_color", "theme_text_color", "text_color"]),
+    ("popover_bg_color", &["view_bg_color", "theme_base_color", "base_color"]),
+    ("popover_fg_color", &["view_fg_color", "theme_text_color", "text_color"]),
+    ("destructive_bg_color", &["error_color", "error_bg_color"]),
+    ("borders", &["borders_color", "unfocused_borders"]),
+    ("success_color", &["success_bg_color"]),
+    ("warning_color", &["warning_bg_color"]),
+    ("error_color", &["error_bg_color"]),
+];
+
+// @import chains deeper than this are ignored
+const MAX_IMPORT_DEPTH: usize = 4;
+// Guards against @a referring to @b referring back to @a
+const MAX_REFERENCE_DEPTH: usize = 16;
+
+#[derive(Clone, Copy)]
+struct Rgba {
+    r: f64,
+    g: f64,
+    b: f64,
+    a: f64,
+}
+
+impl Rgba {
+    fn to_hex(self) -> String {
+        let channel = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        if self.a >= 1.0 {
+            format!("#{:02x}{:02x}{:02x}", channel(self.r), channel(self.g), channel(self.b))
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", channel(self.r), channel(self.g), channel(self.b), channel(self.a))
+        }
+    }
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + 2 + end + 2..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+// Statement text up to the next top-level semicolon
+fn take_statement(text: &str) -> (&str, &str) {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ';' if depth <= 0 => return (&text[..i], &text[i + 1..]),
+            _ => {}
+        }
+    }
+    (text, "")
+}
+
+// Local file an @import points at, resource:// imports can't be followed
+fn import_path(statement: &str, base: &Path) -> Option<PathBuf> {
+    let target = statement.trim();
+    let target = target.strip_prefix("url(").and_then(|t| t.strip_suffix(')')).unwrap_or(target);
+    let target = target.trim().trim_matches(|c| c == '"' || c == '\'');
+    if target.contains("://") && !target.starts_with("file://") {
+        return None;
+    }
+    let target = target.strip_prefix("file://").unwrap_or(target);
+    Some(base.join(target))
+}
+
+// Collect @define-color declarations in order, following local imports where they appear
+fn read_definitions(file: &Path, depth: usize, files: &mut Vec<String>, definitions: &mut Vec<(String, String)>) {
+    if depth > MAX_IMPORT_DEPTH {
+        return;
+    }
+    let css = match fs::read_to_string(file) {
+        Ok(css) => strip_comments(&css),
+        Err(_) => return,
+    };
+    files.push(file.to_string_lossy().to_string());
+    let base = file.parent().unwrap_or(Path::new("/"));
+
+    let mut rest = css.as_str();
+    while let Some(at) = rest.find('@') {
+        rest = &rest[at..];
+        if let Some(after) = rest.strip_prefix("@define-color") {
+            let (statement, remaining) = take_statement(after);
+            if let Some((name, expression)) = statement.trim().split_once(char::is_whitespace) {
+                definitions.push((name.to_string(), expression.trim().to_string()));
+            }
+            rest = remaining;
+        } else if let Some(after) = rest.strip_prefix("@import") {
+            let (statement, remaining) = take_statement(after);
+            if let Some(path) = import_path(statement, base) {
+                read_definitions(&path, depth + 1, files, definitions);
+            }
+            rest = remaining;
+        } else {
+            rest = &rest[1..];
+        }
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Rgba> {
+    // from_str_radix would also take a sign
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize, len: usize| u8::from_str_radix(hex.get(i..i + len)?, 16).ok();
+    let (r, g, b, a) = match hex.len() {
+        3 | 4 => {
+            let short = |i| digit(i, 1).map(|v| v * 17);
+            (short(0)?, short(1)?, short(2)?, if hex.len() == 4 { short(3)? } else { 255 })
+        }
+        6 | 8 => (digit(0, 2)?, digit(2, 2)?, digit(4, 2)?, if hex.len() == 8 { digit(6, 2)? } else { 255 }),
+        _ => return None,
+    };
+    Some(Rgba { r: r as f64 / 255.0, g: g as f64 / 255.0, b: b as f64 / 255.0, a: a as f64 / 255.0 })
+}
+
+fn parse_named(name: &str) -> Option<Rgba> {
+    let hex = match name.to_lowercase().as_str() {
+        "transparent" => return Some(Rgba { r: 0.0, g: 0.0, b: 0.0, a: 0.0 }),
+        "black" => "000000",
+        "white" => "ffffff",
+        "red" => "ff0000",
+        "green" => "008000",
+        "blue" => "0000ff",
+        "yellow" => "ffff00",
+        "orange" => "ffa500",
+        "purple" => "800080",
+        "gray" | "grey" => "808080",
+        _ => return None,
+    };
+    parse_hex(hex)
+}
+
+// Numbers may be written as percentages, channels are out of 255 otherwise
+fn parse_number(value: &str, scale: f64) -> Option<f64> {
+    let value = value.trim();
+    match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().ok().map(|p| p / 100.0),
+        None => value.parse::<f64>().ok().map(|v| v / scale),
+    }
+}
+
+// Arguments of a function call, split at top-level commas
+fn split_args(args: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(args[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(args[start..].trim());
+    parts
+}
+
+fn rgb_to_hls(c: Rgba) -> (f64, f64, f64) {
+    let max = c.r.max(c.g).max(c.b);
+    let min = c.r.min(c.g).min(c.b);
+    let l = (max + min) / 2.0;
+    if max == min {
+        return (0.0, l, 0.0);
+    }
+    let delta = max - min;
+    let s = if l <= 0.5 { delta / (max + min) } else { delta / (2.0 - max - min) };
+    let h = if c.r == max {
+        (c.g - c.b) / delta
+    } else if c.g == max {
+        2.0 + (c.b - c.r) / delta
+    } else {
+        4.0 + (c.r - c.g) / delta
+    };
+    ((h * 60.0).rem_euclid(360.0), l, s)
+}
+
+fn hls_to_rgb(h: f64, l: f64, s: f64, a: f64) -> Rgba {
+    if s == 0.0 {
+        return Rgba { r: l, g: l, b: l, a };
+    }
+    let m2 = if l <= 0.5 { l * (1.0 + s) } else { l + s - l * s };
+    let m1 = 2.0 * l - m2;
+    let channel = |hue: f64| {
+        let hue = hue.rem_euclid(360.0);
+        if hue < 60.0 {
+            m1 + (m2 - m1) * hue / 60.0
+        } else if hue < 180.0 {
+            m2
+        } else if hue < 240.0 {
+            m1 + (m2 - m1) * (240.0 - hue) / 60.0
+        } else {
+            m1
+        }
+    };
+    Rgba { r: channel(h + 120.0), g: channel(h), b: channel(h - 120.0), a }
+}
+
+// Same as GTK: scale lightness and saturation in HLS space
+fn shade(c: Rgba, factor: f64) -> Rgba {
+    let (h, l, s) = rgb_to_hls(c);
+    hls_to_rgb(h, (l * factor).clamp(0.0, 1.0), (s * factor).clamp(0.0, 1.0), c.a)
+}
+
+fn resolve(expression: &str, definitions: &HashMap<String, String>, depth: usize) -> Option<Rgba> {
+    if depth > MAX_REFERENCE_DEPTH {
+        return None;
+    }
+    let expression = expression.trim();
+
+    if let Some(name) = expression.strip_prefix('@') {
+        return resolve(definitions.get(name)?, definitions, depth + 1);
+    }
+    if let Some(hex) = expression.strip_prefix('#') {
+        return parse_hex(hex);
+    }
+
+    let (function, args) = match expression.split_once('(') {
+        Some((function, args)) => (function.trim(), split_args(args.trim_end().strip_suffix(')')?)),
+        None => return parse_named(expression),
+    };
+    let color = |i: usize| args.get(i).and_then(|arg| resolve(arg, definitions, depth + 1));
+    let number = |i: usize| args.get(i).and_then(|arg| parse_number(arg, 1.0));
+
+    match function {
+        "rgb" | "rgba" => {
+            let channel = |i: usize| args.get(i).and_then(|arg| parse_number(arg, 255.0));
+            let a = if args.len() > 3 { number(3)? } else { 1.0 };
+            Some(Rgba { r: channel(0)?, g: channel(1)?, b: channel(2)?, a })
+        }
+        "mix" => {
+            let (a, b, factor) = (color(0)?, color(1)?, number(2)?);
+            let blend = |x: f64, y: f64| x + (y - x) * factor;
+            Some(Rgba { r: blend(a.r, b.r), g: blend(a.g, b.g), b: blend(a.b, b.b), a: blend(a.a, b.a) })
+        }
+        "shade" => Some(shade(color(0)?, number(1)?)),
+        "lighter" => Some(shade(color(0)?, 1.3)),
+        "darker" => Some(shade(color(0)?, 0.7)),
+        "alpha" => {
+            let c = color(0)?;
+            Some(Rgba { a: (c.a * number(1)?).clamp(0.0, 1.0), ..c })
+        }
+        _ => None,
+    }
+}
+
+// Read and resolve one variant from its stylesheets, later files override earlier ones
+pub fn read_variant(files: &[PathBuf]) -> Option<PaletteVariant> {
+    let mut variant = PaletteVariant::default();
+    let mut declarations = Vec::new();
+    for file in files.iter().filter(|file| file.exists()) {
+        read_definitions(file, 0, &mut variant.files, &mut declarations);
+    }
+    if declarations.is_empty() {
+        return None;
+    }
+
+    // The last definition of a name wins, but keep the order names first appeared in
+    let mut definitions: HashMap<String, String> = HashMap::new();
+    let mut order = Vec::new();
+    for (name, expression) in declarations {
+        if definitions.insert(name.clone(), expression).is_none() {
+            order.push(name);
+        }
+    }
+
+    let mut resolved = BTreeMap::new();
+    for name in order {
+        let expression = definitions[&name].clone();
+        let value = resolve(&expression, &definitions, 0).map(Rgba::to_hex);
+        if let Some(value) = &value {
+            resolved.insert(name.clone(), value.clone());
+        }
+        variant.colors.push(PaletteColor { name, expression, value });
+    }
+
+    for (name, fallbacks) in NAMED_COLORS {
+        let value = resolved.get(name)
+            .or_else(|| fallbacks.iter().find_map(|fallback| resolved.get(*fallback)));
+        if let Some(value) = value {
+            variant.named.insert(name.to_string(), value.clone());
+        }
+    }
+
+    Some(variant)
+}
+
+pub fn read_palette(theme_path: &Path) -> ThemePalette {
+    let files = |name: &str| vec![theme_path.join("gtk-3.0").join(name), theme_path.join("gtk-4.0").join(name)];
+    ThemePalette {
+        light: read_variant(&files("gtk.css")),
+        dark: read_variant(&files("gtk-dark.css")),
+    }
+}
+
+#[tauri::command]
+pub fn extract_palette(theme_path: String) -> Result<ThemePalette, String> {
+    let path = Path::new(&theme_path);
+    if !path.is_dir() {
+        return Err(format!("Theme not found at '{}'", theme_path));
+    }
+    Ok(read_palette(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(expression: &str, definitions: &[(&str, &str)]) -> Option<String> {
+        let definitions = definitions.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        resolve(expression, &definitions, 0).map(Rgba::to_hex)
+    }
+
+    // A scratch directory of stylesheets, removed when the test ends
+    struct Stylesheets(PathBuf);
+
+    impl Stylesheets {
+        fn new(test: &str, files: &[(&str, &str)]) -> Stylesheets {
+            let dir = std::env::temp_dir().join(format!("reskin-palette-{}-{}", std::process::id(), test));
+            fs::create_dir_all(&dir).unwrap();
+            for (name, css) in files {
+                fs::write(dir.join(name), css).unwrap();
+            }
+            Stylesheets(dir)
+        }
+
+        fn variant(&self, file: &str) -> PaletteVariant {
+            read_variant(&[self.0.join(file)]).unwrap()
+        }
+    }
+
+    impl Drop for Stylesheets {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn value<'a>(variant: &'a PaletteVariant, name: &str) -> Option<&'a str> {
+        variant.colors.iter().find(|c| c.name == name).and_then(|c| c.value.as_deref())
+    }
+
+    #[test]
+    fn parses_literals() {
+        assert_eq!(eval("#abc", &[]).as_deref(), Some("#aabbcc"));
+        assert_eq!(eval("#aabbcc80", &[]).as_deref(), Some("#aabbcc80"));
+        assert_eq!(eval("rgb(255, 0, 0)", &[]).as_deref(), Some("#ff0000"));
+        assert_eq!(eval("rgba(100%, 0, 0, 0.5)", &[]).as_deref(), Some("#ff000080"));
+        assert_eq!(eval("White", &[]).as_deref(), Some("#ffffff"));
+        assert_eq!(eval("#+1f", &[]), None);
+        assert_eq!(eval("#ééé", &[]), None);
+        assert_eq!(eval("#12345", &[]), None);
+        assert_eq!(eval("cornflowerblue", &[]), None);
+    }
+
+    #[test]
+    fn follows_define_color_chains() {
+        let definitions = [("base", "#336699"), ("accent", "@base"), ("selected", "@accent"), ("missing", "@nowhere")];
+        assert_eq!(eval("@selected", &definitions).as_deref(), Some("#336699"));
+        assert_eq!(eval("@missing", &definitions), None);
+    }
+
+    #[test]
+    fn evaluates_functions() {
+        let definitions = [("black", "#000000"), ("white", "#ffffff"), ("gray", "#808080")];
+        assert_eq!(eval("mix(@black, @white, 0.5)", &definitions).as_deref(), Some("#808080"));
+        assert_eq!(eval("mix(@black, @white, 25%)", &definitions).as_deref(), Some("#404040"));
+        assert_eq!(eval("shade(@gray, 0.5)", &definitions).as_deref(), Some("#404040"));
+        assert_eq!(eval("shade(@gray, 3)", &definitions).as_deref(), Some("#ffffff"));
+        // Saturation is scaled too, as in GTK
+        assert_eq!(eval("shade(#ff0000, 0.5)", &[]).as_deref(), Some("#602020"));
+        assert_eq!(eval("darker(@white)", &definitions).as_deref(), Some("#b3b3b3"));
+        assert_eq!(eval("alpha(@white, 0.5)", &definitions).as_deref(), Some("#ffffff80"));
+        assert_eq!(eval("alpha(#ffffff80, 0.5)", &[]).as_deref(), Some("#ffffff40"));
+        assert_eq!(eval("alpha(mix(@black, @white, 0.5), 0.5)", &definitions).as_deref(), Some("#80808080"));
+        assert_eq!(eval("mix(@black, @white)", &definitions), None);
+        assert_eq!(eval("blend(@black, @white, 0.5)", &definitions), None);
+    }
+
+    #[test]
+    fn tolerates_whitespace() {
+        let definitions = [("white", "#ffffff")];
+        assert_eq!(eval("alpha( @white , 0.5 ) ", &definitions).as_deref(), Some("#ffffff80"));
+        assert_eq!(eval("alpha(@white, 0.5)\n\t", &definitions).as_deref(), Some("#ffffff80"));
+        assert_eq!(eval("alpha (@white, shade(0.5 ) )", &definitions), None);
+        assert_eq!(eval("shade(@white, 0.5 )\t", &definitions).as_deref(), Some("#808080"));
+    }
+
+    #[test]
+    fn limits_reference_depth() {
+        // Resolving @cN follows N + 1 references, the last one to c0's literal
+        let names: Vec<String> = (0..=MAX_REFERENCE_DEPTH).map(|i| format!("c{}", i)).collect();
+        let mut definitions = vec![(names[0].clone(), "#ffffff".to_string())];
+        for i in 1..names.len() {
+            definitions.push((names[i].clone(), format!("@{}", names[i - 1])));
+        }
+        let definitions: Vec<(&str, &str)> = definitions.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+
+        let deepest = format!("@{}", names[MAX_REFERENCE_DEPTH - 1]);
+        assert_eq!(eval(&deepest, &definitions).as_deref(), Some("#ffffff"));
+        let too_deep = format!("@{}", names[MAX_REFERENCE_DEPTH]);
+        assert_eq!(eval(&too_deep, &definitions), None);
+
+        // Cycles run into the same limit instead of overflowing the stack
+        assert_eq!(eval("@a", &[("a", "@b"), ("b", "shade(@a, 0.5)")]), None);
+    }
+
+    #[test]
+    fn reads_definitions_across_imports() {
+        let sheets = Stylesheets::new("imports", &[
+            ("gtk.css", "/* @define-color commented #000; */\n@import url(\"colors.css\");\n@import 'resource:///org/gtk/theme.css';\n@define-color accent_bg_color shade(@blue, 1.0);\nwindow { color: @theme_fg_color; }\n@define-color theme_bg_color #ffffff;\n"),
+            ("colors.css", "@define-color blue #3584e4;\n@define-color theme_bg_color #000000;\n@define-color theme_fg_color mix(@blue, #ffffff, 0.5);"),
+        ]);
+        let variant = sheets.variant("gtk.css");
+
+        assert_eq!(variant.files.len(), 2);
+        let names: Vec<&str> = variant.colors.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["blue", "theme_bg_color", "theme_fg_color", "accent_bg_color"]);
+        // The later declaration wins
+        assert_eq!(value(&variant, "theme_bg_color"), Some("#ffffff"));
+        assert_eq!(value(&variant, "accent_bg_color"), Some("#3584e4"));
+        assert_eq!(variant.named.get("window_bg_color").map(String::as_str), Some("#ffffff"));
+        assert_eq!(variant.named.get("accent_color").map(String::as_str), Some("#3584e4"));
+        assert!(!variant.named.contains_key("view_bg_color"));
+    }
+
+    #[test]
+    fn limits_import_depth() {
+        let files: Vec<(String, String)> = (0..=MAX_IMPORT_DEPTH + 1)
+            .map(|i| (format!("{}.css", i), format!("@import \"{}.css\";\n@define-color c{} #ffffff;", i + 1, i)))
+            .collect();
+        let files: Vec<(&str, &str)> = files.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        let sheets = Stylesheets::new("depth", &files);
+        let variant = sheets.variant("0.css");
+
+        assert_eq!(variant.files.len(), MAX_IMPORT_DEPTH + 1);
+        assert!(value(&variant, &format!("c{}", MAX_IMPORT_DEPTH)).is_some());
+        assert!(value(&variant, &format!("c{}", MAX_IMPORT_DEPTH + 1)).is_none());
+
+        // A stylesheet importing itself stops at the same depth
+        let sheets = Stylesheets::new("self", &[("gtk.css", "@import \"gtk.css\"; @define-color a #000;")]);
+        assert_eq!(sheets.variant("gtk.css").files.len(), MAX_IMPORT_DEPTH + 1);
+    }
+}
@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::settings::load_settings;
+use crate::types::InstallLocation;
+
+// Get the user's home directory
+pub fn home_dir() -> Result<PathBuf, String> {
+    std::env::var("HOME")
+        .map(PathBuf::from)
+        .map_err(|_| "Failed to get HOME directory".to_string())
+}
+
+// Resolve an XDG base directory, ignoring relative values as the spec requires
+fn xdg_dir(var: &str, fallback: &str) -> Result<PathBuf, String> {
+    match std::env::var(var) {
+        Ok(dir) if dir.starts_with('/') => Ok(PathBuf::from(dir)),
+        _ => Ok(home_dir()?.join(fallback)),
+    }
+}
+
+// $XDG_DATA_HOME, defaults to ~/.local/share
+pub fn data_home() -> Result<PathBuf, String> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+// $XDG_CONFIG_HOME, defaults to ~/.config
+pub fn config_home() -> Result<PathBuf, String> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+// $XDG_CACHE_HOME, defaults to ~/.cache
+pub fn cache_home() -> Result<PathBuf, String> {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+static WORK_DIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// Fresh directory for temporary work, unique per process and call so nested or
+// concurrent installs never share one
+pub fn work_dir(kind: &str) -> Result<PathBuf, String> {
+    let id = WORK_DIR_COUNTER.fetch_add(1, Ordering::Relaxed);
+    Ok(cache_home()?.join("reskin").join(format!("{}-{}-{}", kind, std::process::id(), id)))
+}
+
+// Directory where reskin keeps its own configuration (settings, recent themes)
+pub fn reskin_config_dir() -> Result<PathBuf, String> {
+    Ok(config_home()?.join("reskin"))
+}
+
+// Directory for GTK/window manager themes
+pub fn themes_dir_for(location: InstallLocation) -> Result<PathBuf, String> {
+    match location {
+        InstallLocation::Default | InstallLocation::Legacy => Ok(home_dir()?.join(".themes")),
+        InstallLocation::Xdg => Ok(data_home()?.join("themes")),
+    }
+}
+
+// Directory for icon and cursor themes
+pub fn icons_dir_for(location: InstallLocation) -> Result<PathBuf, String> {
+    match location {
+        InstallLocation::Legacy => Ok(home_dir()?.join(".icons")),
+        InstallLocation::Default | InstallLocation::Xdg => Ok(data_home()?.join("icons")),
+    }
+}
+
+// Themes directory for the configured install location
+pub fn themes_dir() -> Result<PathBuf, String> {
+    themes_dir_for(load_settings().install_location)
+}
+
+// Icons directory for the configured install location
+pub fn icons_dir() -> Result<PathBuf, String> {
+    icons_dir_for(load_settings().install_location)
+}
+
+// Fonts always go to $XDG_DATA_HOME/fonts, ~/.fonts is deprecated by fontconfig
+pub fn fonts_dir() -> Result<PathBuf, String> {
+    Ok(data_home()?.join("fonts"))
+}
+
+// Where GTK would find a theme by name: user directories first, then system ones
+pub fn find_theme_dir(theme_name: &str) -> Option<PathBuf> {
+    let user = [InstallLocation::Xdg, InstallLocation::Legacy].into_iter()
+        .filter_map(|location| themes_dir_for(location).ok());
+    let system = ["/usr/local/share/themes", "/usr/share/themes"].into_iter().map(PathBuf::from);
+    user.chain(system)
+        .map(|dir| dir.join(theme_name))
+        .find(|dir| dir.is_dir())
+}
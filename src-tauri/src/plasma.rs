@@ -0,0 +1,149 @@
+// KDE Plasma assets bundled with a theme: color schemes, Aurorae decorations, desktop themes and global themes
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::paths::data_home;
+use crate::progress::Progress;
+use crate::utils::copy_dir_with_progress;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PlasmaAssetKind {
+    ColorScheme,  // <Name>.colors
+    Aurorae,      // Window decoration with decoration.svg(z)
+    DesktopTheme, // Panel and widget styling
+    LookAndFeel,  // Global theme package
+}
+
+pub struct PlasmaAsset {
+    pub kind: PlasmaAssetKind,
+    pub name: String,
+    pub source: PathBuf,
+}
+
+// Subdirectories of a theme the assets may live in, mirroring ~/.local/share or flattened
+fn source_dirs(kind: PlasmaAssetKind) -> &'static [&'static str] {
+    match kind {
+        PlasmaAssetKind::ColorScheme => &["", "color-schemes"],
+        PlasmaAssetKind::Aurorae => &["aurorae/themes", "aurorae"],
+        PlasmaAssetKind::DesktopTheme => &["plasma/desktoptheme", "desktoptheme"],
+        PlasmaAssetKind::LookAndFeel => &["plasma/look-and-feel", "look-and-feel"],
+    }
+}
+
+// Where Plasma looks for user-installed assets of each kind
+pub fn install_dir(kind: PlasmaAssetKind) -> Result<PathBuf, String> {
+    let data = data_home()?;
+    Ok(match kind {
+        PlasmaAssetKind::ColorScheme => data.join("color-schemes"),
+        PlasmaAssetKind::Aurorae => data.join("aurorae/themes"),
+        PlasmaAssetKind::DesktopTheme => data.join("plasma/desktoptheme"),
+        PlasmaAssetKind::LookAndFeel => data.join("plasma/look-and-feel"),
+    })
+}
+
+pub const KINDS: [PlasmaAssetKind; 4] = [
+    PlasmaAssetKind::LookAndFeel,
+    PlasmaAssetKind::DesktopTheme,
+    PlasmaAssetKind::ColorScheme,
+    PlasmaAssetKind::Aurorae,
+];
+
+fn has_metadata(dir: &Path) -> bool {
+    dir.join("metadata.json").is_file() || dir.join("metadata.desktop").is_file()
+}
+
+fn is_asset(kind: PlasmaAssetKind, path: &Path) -> bool {
+    match kind {
+        PlasmaAssetKind::ColorScheme => path.is_file() && path.extension().is_some_and(|e| e == "colors"),
+        PlasmaAssetKind::Aurorae => path.join("decoration.svg").is_file() || path.join("decoration.svgz").is_file(),
+        PlasmaAssetKind::DesktopTheme => has_metadata(path) && path.join("widgets").is_dir(),
+        PlasmaAssetKind::LookAndFeel => has_metadata(path) && path.join("contents").is_dir(),
+    }
+}
+
+fn asset_name(path: &Path) -> String {
+    let name = if path.is_file() { path.file_stem() } else { path.file_name() };
+    name.map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+// Every Plasma asset in a theme directory, in the order they should be applied
+pub fn find_plasma_assets(theme_path: &Path) -> Vec<PlasmaAsset> {
+    let mut assets: Vec<PlasmaAsset> = Vec::new();
+    for kind in KINDS {
+        for dir in source_dirs(kind) {
+            let mut entries: Vec<PathBuf> = match fs::read_dir(theme_path.join(dir)) {
+                Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+                Err(_) => continue,
+            };
+            entries.sort();
+            for path in entries.into_iter().filter(|path| is_asset(kind, path)) {
+                let name = asset_name(&path);
+                if !assets.iter().any(|a| a.kind == kind && a.name == name) {
+                    assets.push(PlasmaAsset { kind, name, source: path });
+                }
+            }
+        }
+    }
+    assets
+}
+
+// Copy the assets into ~/.local/share, replacing only paths this theme installed before
+pub fn install_plasma_assets(theme_path: &Path, owned: &[String], progress: &mut Progress) -> Result<Vec<PathBuf>, String> {
+    let mut paths = Vec::new();
+    for asset in find_plasma_assets(theme_path) {
+        progress.check_cancelled()?;
+        let dir = install_dir(asset.kind)?;
+        let file_name = asset.source.file_name().ok_or("Invalid asset path")?;
+        let dest = dir.join(file_name);
+
+        if dest.exists() {
+            if !owned.contains(&dest.to_string_lossy().to_string()) {
+                return Err(format!("{} already exists and was not installed by this theme", dest.display()));
+            }
+            let removed = if dest.is_dir() { fs::remove_dir_all(&dest) } else { fs::remove_file(&dest) };
+            removed.map_err(|e| format!("Failed to remove {}: {}", dest.display(), e))?;
+        }
+
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        progress.track_cleanup(dest.clone());
+        if asset.source.is_dir() {
+            copy_dir_with_progress(&asset.source.to_string_lossy(), &dest.to_string_lossy(), progress)
+                .map_err(|e| format!("Failed to install {}: {}", asset.name, e))?;
+        } else {
+            fs::copy(&asset.source, &dest)
+                .map_err(|e| format!("Failed to install {}: {}", asset.name, e))?;
+        }
+        paths.push(dest);
+    }
+    Ok(paths)
+}
+
+// The assets among an install's recorded paths
+pub fn installed_plasma_assets(paths: &[String]) -> Vec<(PlasmaAssetKind, String)> {
+    let mut assets = Vec::new();
+    for kind in KINDS {
+        let dir = match install_dir(kind) {
+            Ok(dir) => dir,
+            Err(_) => continue,
+        };
+        for path in paths.iter().map(Path::new).filter(|path| path.parent() == Some(dir.as_path())) {
+            let name = match kind {
+                PlasmaAssetKind::ColorScheme => path.file_stem(),
+                _ => path.file_name(),
+            };
+            if let Some(name) = name {
+                assets.push((kind, name.to_string_lossy().to_string()));
+            }
+        }
+    }
+    assets
+}
+
+pub fn asset_label(kind: PlasmaAssetKind) -> &'static str {
+    match kind {
+        PlasmaAssetKind::ColorScheme => "Plasma color scheme",
+        PlasmaAssetKind::Aurorae => "Aurorae decoration",
+        PlasmaAssetKind::DesktopTheme => "Plasma desktop theme",
+        PlasmaAssetKind::LookAndFeel => "Plasma global theme",
+    }
+}
@@ -0,0 +1,446 @@
+// Preview images drawn on the CPU: a mock window in the theme's colors with its icons and cursor
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use tauri::ipc::Response;
+use crate::cursors::read_xcursor_image;
+use crate::extract::is_plain_name;
+use crate::palette::read_palette;
+use crate::paths::{cache_home, find_theme_dir};
+
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 400;
+pub const PREVIEW_FILE: &str = "preview.png";
+
+// Icons looked up for the sidebar, first match wins in each group
+const SAMPLE_ICONS: [&[&str]; 5] = [
+    &["user-home", "folder-home", "go-home"],
+    &["folder-documents", "folder"],
+    &["folder-pictures", "folder-images", "image-x-generic"],
+    &["folder-music", "audio-x-generic"],
+    &["user-trash", "user-trash-full"],
+];
+
+// Don't walk huge icon themes forever looking for samples
+const MAX_ICON_FILES: usize = 20000;
+
+#[derive(Clone, Copy)]
+struct Color([u8; 4]);
+
+impl Color {
+    fn parse(hex: &str) -> Option<Color> {
+        let hex = hex.strip_prefix('#')?;
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+        Some(Color([channel(0)?, channel(2)?, channel(4)?, alpha]))
+    }
+
+    fn with_alpha(self, alpha: f32) -> Color {
+        let [r, g, b, a] = self.0;
+        Color([r, g, b, (a as f32 * alpha) as u8])
+    }
+
+    fn mix(self, other: Color, factor: f32) -> Color {
+        let blend = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * factor) as u8;
+        Color([blend(self.0[0], other.0[0]), blend(self.0[1], other.0[1]), blend(self.0[2], other.0[2]), blend(self.0[3], other.0[3])])
+    }
+}
+
+// Colors the mock window uses, Adwaita's when the theme doesn't define them
+struct Scheme {
+    window_bg: Color,
+    window_fg: Color,
+    view_bg: Color,
+    view_fg: Color,
+    headerbar_bg: Color,
+    headerbar_fg: Color,
+    accent_bg: Color,
+    accent_fg: Color,
+    destructive: Color,
+}
+
+impl Scheme {
+    fn from_theme(theme_path: &Path) -> Scheme {
+        let palette = read_palette(theme_path);
+        let named = palette.light.or(palette.dark).map(|variant| variant.named).unwrap_or_default();
+        let color = |name: &str, fallback: &str| named.get(name)
+            .and_then(|hex| Color::parse(hex))
+            .unwrap_or_else(|| Color::parse(fallback).unwrap());
+
+        Scheme {
+            window_bg: color("window_bg_color", "#fafafa"),
+            window_fg: color("window_fg_color", "#323232"),
+            view_bg: color("view_bg_color", "#ffffff"),
+            view_fg: color("view_fg_color", "#323232"),
+            headerbar_bg: color("headerbar_bg_color", "#ebebeb"),
+            headerbar_fg: color("headerbar_fg_color", "#323232"),
+            accent_bg: color("accent_bg_color", "#3584e4"),
+            accent_fg: color("accent_fg_color", "#ffffff"),
+            destructive: color("destructive_bg_color", "#e01b24"),
+        }
+    }
+}
+
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>, // Straight RGBA
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Canvas {
+        Canvas { width, height, pixels: vec![0; (width * height * 4) as usize] }
+    }
+
+    // Source-over blend, coverage scales the color's alpha for anti-aliased edges
+    fn blend(&mut self, x: i32, y: i32, color: Color, coverage: f32) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let i = ((y as u32 * self.width + x as u32) * 4) as usize;
+        let src_a = color.0[3] as f32 / 255.0 * coverage.clamp(0.0, 1.0);
+        let dst_a = self.pixels[i + 3] as f32 / 255.0;
+        let out_a = src_a + dst_a * (1.0 - src_a);
+        if out_a <= 0.0 {
+            return;
+        }
+        for c in 0..3 {
+            let src = color.0[c] as f32;
+            let dst = self.pixels[i + c] as f32;
+            self.pixels[i + c] = ((src * src_a + dst * dst_a * (1.0 - src_a)) / out_a).round() as u8;
+        }
+        self.pixels[i + 3] = (out_a * 255.0).round() as u8;
+    }
+
+    fn fill_rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: Color) {
+        for py in y..y + h {
+            for px in x..x + w {
+                self.blend(px, py, color, 1.0);
+            }
+        }
+    }
+
+    // Rectangle with corners of the given radius, edge pixels get partial coverage
+    fn fill_rounded_rect(&mut self, x: i32, y: i32, w: i32, h: i32, radius: f32, color: Color) {
+        for py in y..y + h {
+            for px in x..x + w {
+                let cx = (px as f32 + 0.5).clamp(x as f32 + radius, (x + w) as f32 - radius);
+                let cy = (py as f32 + 0.5).clamp(y as f32 + radius, (y + h) as f32 - radius);
+                let distance = ((px as f32 + 0.5 - cx).powi(2) + (py as f32 + 0.5 - cy).powi(2)).sqrt();
+                self.blend(px, py, color, radius - distance + 0.5);
+            }
+        }
+    }
+
+    fn fill_circle(&mut self, cx: f32, cy: f32, radius: f32, color: Color) {
+        let (x, y, size) = ((cx - radius) as i32, (cy - radius) as i32, (radius * 2.0) as i32 + 2);
+        self.fill_rounded_rect(x, y, size, size, radius, color);
+    }
+
+    // Scale an RGBA image into the box by averaging the source pixels each target pixel covers
+    fn draw_image(&mut self, image: &Image, x: i32, y: i32, w: u32, h: u32) {
+        for ty in 0..h {
+            for tx in 0..w {
+                let (x0, x1) = (tx * image.width / w, ((tx + 1) * image.width / w).max(tx * image.width / w + 1));
+                let (y0, y1) = (ty * image.height / h, ((ty + 1) * image.height / h).max(ty * image.height / h + 1));
+                let mut sum = [0f32; 4];
+                let mut count = 0f32;
+                for sy in y0..y1.min(image.height) {
+                    for sx in x0..x1.min(image.width) {
+                        let i = ((sy * image.width + sx) * 4) as usize;
+                        let a = image.pixels[i + 3] as f32;
+                        for (total, value) in sum.iter_mut().zip(&image.pixels[i..i + 3]) {
+                            *total += *value as f32 * a;
+                        }
+                        sum[3] += a;
+                        count += 1.0;
+                    }
+                }
+                if sum[3] <= 0.0 {
+                    continue;
+                }
+                let color = Color([
+                    (sum[0] / sum[3]) as u8,
+                    (sum[1] / sum[3]) as u8,
+                    (sum[2] / sum[3]) as u8,
+                    (sum[3] / count) as u8,
+                ]);
+                self.blend(x + tx as i32, y + ty as i32, color, 1.0);
+            }
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, x: i32, y: i32, scale: i32, color: Color) {
+        for (n, c) in text.chars().enumerate() {
+            let glyph = glyph(c);
+            let gx = x + n as i32 * 6 * scale;
+            for (row, bits) in glyph.iter().enumerate() {
+                for col in 0..5 {
+                    if bits & (0b10000 >> col) != 0 {
+                        self.fill_rect(gx + col * scale, y + row as i32 * scale, scale, scale, color);
+                    }
+                }
+            }
+        }
+    }
+
+    fn encode_png(&self) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut out, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().map_err(|e| format!("Failed to encode preview: {}", e))?;
+            writer.write_image_data(&self.pixels).map_err(|e| format!("Failed to encode preview: {}", e))?;
+        }
+        Ok(out)
+    }
+}
+
+// 5x7 bitmap glyphs for the window title, lowercase is drawn as uppercase
+fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        'A' => [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+        'D' => [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'G' => [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'J' => [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'P' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+        'Q' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'V' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'W' => [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+        'X' => [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+        'Y' => [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100],
+        'Z' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        '-' => [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000],
+        '.' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100],
+        '_' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111],
+        ' ' => [0; 7],
+        _ => [0b11111, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11111],
+    }
+}
+
+struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>, // Straight RGBA
+}
+
+fn decode_png(path: &Path) -> Option<Image> {
+    let mut decoder = png::Decoder::new(File::open(path).ok()?);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().ok()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).ok()?;
+    buffer.truncate(info.buffer_size());
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => buffer.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => buffer.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => buffer.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+        png::ColorType::Indexed => return None, // EXPAND turns palettes into RGB(A)
+    };
+    Some(Image { width: info.width, height: info.height, pixels })
+}
+
+// PNG icons in the theme by name, SVG-only themes have no samples since there's no rasterizer here
+fn find_icon_files(dir: &Path, found: &mut Vec<PathBuf>) {
+    let mut entries: Vec<_> = match fs::read_dir(dir) {
+        Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+        Err(_) => return,
+    };
+    entries.sort();
+    for path in entries {
+        if found.len() >= MAX_ICON_FILES {
+            return;
+        }
+        if path.is_dir() {
+            find_icon_files(&path, found);
+        } else if path.extension().is_some_and(|ext| ext == "png") {
+            found.push(path);
+        }
+    }
+}
+
+// One icon per sample group, the one closest to the size it's drawn at
+fn sample_icons(theme_path: &Path, size: u32) -> Vec<Option<Image>> {
+    let mut files = Vec::new();
+    find_icon_files(theme_path, &mut files);
+
+    SAMPLE_ICONS.iter().map(|names| {
+        names.iter().find_map(|name| {
+            files.iter()
+                .filter(|file| file.file_stem().is_some_and(|stem| stem == *name))
+                .filter_map(|file| decode_png(file))
+                .min_by_key(|image| image.width.abs_diff(size * 2)) // Prefer 2x for a crisper downscale
+        })
+    }).collect()
+}
+
+fn cursor_image(theme_path: &Path) -> Option<Image> {
+    ["left_ptr", "default", "arrow"].iter().find_map(|name| {
+        let data = fs::read(theme_path.join("cursors").join(name)).ok()?;
+        let (width, height, pixels) = read_xcursor_image(&data, 32)?;
+        Some(Image { width, height, pixels })
+    })
+}
+
+// Render the preview for a theme directory as PNG bytes, titled with the theme name
+pub fn render_preview(theme_path: &Path, name: &str) -> Result<Vec<u8>, String> {
+    if !theme_path.is_dir() {
+        return Err(format!("Theme not found at '{}'", theme_path.display()));
+    }
+    let scheme = Scheme::from_theme(theme_path);
+    let mut canvas = Canvas::new(WIDTH, HEIGHT);
+
+    // Backdrop tinted with the accent so light and dark themes both stand out
+    let backdrop = scheme.accent_bg.mix(Color([30, 30, 36, 255]), 0.65);
+    canvas.fill_rect(0, 0, WIDTH as i32, HEIGHT as i32, backdrop);
+
+    let (wx, wy, ww, wh) = (40, 30, 560, 340);
+    canvas.fill_rounded_rect(wx + 2, wy + 6, ww, wh, 12.0, Color([0, 0, 0, 70])); // Shadow
+    canvas.fill_rounded_rect(wx, wy, ww, wh, 12.0, scheme.view_bg);
+
+    // Titlebar with the theme name and window buttons
+    let header_h = 44;
+    canvas.fill_rounded_rect(wx, wy, ww, header_h + 12, 12.0, scheme.headerbar_bg);
+    canvas.fill_rect(wx, wy + header_h, ww, 12, scheme.view_bg);
+    canvas.fill_rect(wx, wy + header_h - 1, ww, 1, scheme.headerbar_fg.with_alpha(0.15));
+    let title: String = name.chars().take(24).collect();
+    let title_w = title.chars().count() as i32 * 12;
+    canvas.draw_text(&title, wx + (ww - title_w) / 2, wy + 15, 2, scheme.headerbar_fg);
+    let button_y = (wy + header_h / 2) as f32;
+    let right = (wx + ww) as f32;
+    canvas.fill_circle(right - 24.0, button_y, 9.0, scheme.destructive);
+    canvas.fill_circle(right - 50.0, button_y, 9.0, scheme.headerbar_fg.with_alpha(0.15));
+    canvas.fill_circle(right - 76.0, button_y, 9.0, scheme.headerbar_fg.with_alpha(0.15));
+
+    // Sidebar rows with icon samples, the second one selected
+    let sidebar_w = 170;
+    let body_y = wy + header_h;
+    let body_h = wh - header_h;
+    canvas.fill_rounded_rect(wx, body_y, sidebar_w, body_h, 12.0, scheme.window_bg);
+    canvas.fill_rect(wx, body_y, sidebar_w, body_h - 12, scheme.window_bg);
+    canvas.fill_rect(wx + sidebar_w - 12, body_y, 12, body_h, scheme.window_bg);
+    canvas.fill_rect(wx + sidebar_w, body_y, 1, body_h, scheme.window_fg.with_alpha(0.12));
+
+    let icon_size = 24;
+    for (row, icon) in sample_icons(theme_path, icon_size).iter().enumerate() {
+        let ry = body_y + 12 + row as i32 * 40;
+        let selected = row == 1;
+        if selected {
+            canvas.fill_rounded_rect(wx + 8, ry, sidebar_w - 16, 34, 6.0, scheme.accent_bg);
+        }
+        let fg = if selected { scheme.accent_fg } else { scheme.window_fg };
+        match icon {
+            Some(image) => canvas.draw_image(image, wx + 18, ry + 5, icon_size, icon_size),
+            None => canvas.fill_rounded_rect(wx + 18, ry + 5, icon_size as i32, icon_size as i32, 5.0, fg.with_alpha(0.35)),
+        }
+        canvas.fill_rounded_rect(wx + 52, ry + 13, 70 + (row as i32 * 17) % 30, 8, 4.0, fg.with_alpha(0.7));
+    }
+
+    // Content: a heading, lines of body text, a card and the suggested action button
+    let cx = wx + sidebar_w + 24;
+    let cw = ww - sidebar_w - 48;
+    canvas.fill_rounded_rect(cx, body_y + 24, 180, 14, 7.0, scheme.view_fg.with_alpha(0.85));
+    for (line, width) in [cw, cw - 40, cw - 10, cw - 90].iter().enumerate() {
+        canvas.fill_rounded_rect(cx, body_y + 54 + line as i32 * 18, *width, 8, 4.0, scheme.view_fg.with_alpha(0.45));
+    }
+    let card_y = body_y + 140;
+    canvas.fill_rounded_rect(cx, card_y, cw, 70, 10.0, scheme.view_fg.with_alpha(0.06));
+    canvas.fill_rounded_rect(cx + 16, card_y + 18, 120, 10, 5.0, scheme.view_fg.with_alpha(0.7));
+    canvas.fill_rounded_rect(cx + 16, card_y + 40, 200, 8, 4.0, scheme.view_fg.with_alpha(0.4));
+    canvas.fill_rounded_rect(cx + cw - 110, body_y + body_h - 54, 110, 34, 8.0, scheme.accent_bg);
+    canvas.fill_rounded_rect(cx + cw - 85, body_y + body_h - 41, 60, 8, 4.0, scheme.accent_fg);
+
+    // Cursor hovering over the button
+    if let Some(cursor) = cursor_image(theme_path) {
+        let (w, h) = (cursor.width.min(48), cursor.height.min(48));
+        canvas.draw_image(&cursor, cx + cw - 60, body_y + body_h - 36, w, h);
+    }
+
+    canvas.encode_png()
+}
+
+// Cached previews live in $XDG_CACHE_HOME/reskin/previews/<theme>.png
+pub fn cached_preview_path(theme_name: &str) -> Result<PathBuf, String> {
+    Ok(cache_home()?.join("reskin").join("previews").join(format!("{}.png", theme_name)))
+}
+
+// A preview that already exists: the one the bundle shipped, then the cached render.
+// Reinstalling recreates the theme directory, a render older than it is stale
+pub fn existing_preview(theme_path: &Path) -> Option<PathBuf> {
+    let embedded = theme_path.join(PREVIEW_FILE);
+    if embedded.is_file() {
+        return Some(embedded);
+    }
+    let name = theme_path.file_name()?.to_string_lossy().to_string();
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    cached_preview_path(&name).ok()
+        .filter(|cached| cached.is_file())
+        .filter(|cached| modified(cached) >= modified(theme_path))
+}
+
+pub fn remove_cached_preview(theme_name: &str) {
+    if let Ok(cached) = cached_preview_path(theme_name) {
+        let _ = fs::remove_file(cached);
+    }
+}
+
+fn render_to_cache(theme_path: &Path) -> Result<PathBuf, String> {
+    let name = theme_path.file_name().ok_or("Invalid theme path")?.to_string_lossy().to_string();
+    let png = render_preview(theme_path, &name)?;
+
+    let cache_path = cached_preview_path(&name)?;
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create preview cache: {}", e))?;
+    }
+    fs::write(&cache_path, png).map_err(|e| format!("Failed to write preview: {}", e))?;
+    Ok(cache_path)
+}
+
+// Render a theme's preview into the cache and return its path
+#[tauri::command(async)]
+pub fn generate_preview(theme_path: String) -> Result<String, String> {
+    Ok(render_to_cache(Path::new(&theme_path))?.to_string_lossy().to_string())
+}
+
+// PNG bytes for a theme card, rendering into the cache only when there is no preview yet
+#[tauri::command(async)]
+pub fn get_theme_preview(theme_name: String) -> Result<Response, String> {
+    // Marketplace names come from the network
+    if !is_plain_name(&theme_name) {
+        return Err(format!("Invalid theme name: {}", theme_name));
+    }
+    let theme_path = find_theme_dir(&theme_name).ok_or(format!("Theme '{}' not found", theme_name))?;
+    let preview = match existing_preview(&theme_path) {
+        Some(preview) => preview,
+        None => render_to_cache(&theme_path)?,
+    };
+    let png = fs::read(&preview).map_err(|e| format!("Failed to read preview: {}", e))?;
+    Ok(Response::new(png))
+}
@@ -0,0 +1,116 @@
+// Progress reporting for long-running operations, emitted to the UI as "progress" events
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use crate::jobs::{JobHandle, JobManager, CANCELLED};
+use crate::types::ProgressEvent;
+
+// Don't flood the webview with an event per file on large icon packs
+const EMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct Progress {
+    app: Option<AppHandle>,
+    job: Option<JobHandle>,
+    event: ProgressEvent,
+    last_emit: Option<Instant>,
+}
+
+impl Progress {
+    // Registers a job so the operation shows up in list_jobs and can be cancelled
+    pub fn new(app: &AppHandle, jobs: &JobManager, operation: &str, description: &str) -> Progress {
+        let job = jobs.start(operation, description);
+        Progress {
+            app: Some(app.clone()),
+            event: ProgressEvent { job_id: Some(job.id), operation: operation.to_string(), ..Default::default() },
+            job: Some(job),
+            last_emit: None,
+        }
+    }
+
+    // For callers that have no UI to report to
+    pub fn none() -> Progress {
+        Progress { app: None, job: None, event: ProgressEvent::default(), last_emit: None }
+    }
+
+    // Called between units of work, fails once the job has been cancelled
+    pub fn check_cancelled(&self) -> Result<(), String> {
+        match &self.job {
+            Some(job) if job.is_cancelled() => Err(CANCELLED.to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    // Temp directories and partial output removed if the job fails or is cancelled. Call it
+    // before creating the path, anything already there belongs to someone else
+    pub fn track_cleanup(&self, path: PathBuf) {
+        if path.exists() {
+            return;
+        }
+        if let Some(job) = &self.job {
+            job.track_cleanup(path);
+        }
+    }
+
+    // Position in the cleanup list, see keep_cleanup
+    pub fn cleanup_mark(&self) -> usize {
+        self.job.as_ref().map_or(0, |job| job.cleanup_len())
+    }
+
+    // A nested step finished for good, e.g. a dependency that is now in the registry:
+    // what it tracked since the mark stays even if the rest of the job fails
+    pub fn keep_cleanup(&self, mark: usize) {
+        if let Some(job) = &self.job {
+            job.keep_cleanup(mark);
+        }
+    }
+
+    // Start a new phase, totals of 0 mean the amount of work isn't known
+    pub fn phase(&mut self, phase: &str, bytes_total: u64, files_total: u64) {
+        self.event.phase = phase.to_string();
+        self.event.bytes_done = 0;
+        self.event.bytes_total = bytes_total;
+        self.event.files_done = 0;
+        self.event.files_total = files_total;
+        self.event.current_file = None;
+        self.emit(true);
+    }
+
+    pub fn set_file(&mut self, file: &str) {
+        self.event.current_file = Some(file.to_string());
+        self.emit(false);
+    }
+
+    pub fn add(&mut self, bytes: u64, files: u64) {
+        self.event.bytes_done += bytes;
+        self.event.files_done += files;
+        self.emit(false);
+    }
+
+    pub fn finish<T>(&mut self, result: &Result<T, String>) {
+        self.event.phase = match result {
+            Ok(_) => "done".to_string(),
+            Err(_) if self.check_cancelled().is_err() => "cancelled".to_string(),
+            Err(_) => "failed".to_string(),
+        };
+        self.event.current_file = None;
+        self.emit(true);
+        if let Some(job) = &self.job {
+            job.finish(result.as_ref().err().map(|e| e.as_str()));
+        }
+    }
+
+    fn emit(&mut self, force: bool) {
+        let app = match &self.app {
+            Some(app) => app,
+            None => return,
+        };
+        if !force && self.last_emit.is_some_and(|last| last.elapsed() < EMIT_INTERVAL) {
+            return;
+        }
+        let _ = app.emit("progress", self.event.clone());
+        if let Some(job) = &self.job {
+            job.update_progress(&self.event);
+        }
+        self.last_emit = Some(Instant::now());
+    }
+}
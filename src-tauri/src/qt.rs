@@ -0,0 +1,365 @@
+// Qt theming outside Plasma: Kvantum themes and qt5ct/qt6ct color schemes built from the GTK palette
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::cache::command_exists;
+use crate::desktop::{accent_hex, desktop_is, parse_accent};
+use crate::palette::read_palette;
+use crate::paths::{config_home, find_theme_dir};
+use crate::progress::Progress;
+use crate::settings::{load_settings, save_settings};
+use crate::utils::copy_dir_with_progress;
+
+// Snapshot key for the active Kvantum theme
+pub const KVANTUM: &str = "kvantum";
+
+// File name of the generated qt5ct/qt6ct color scheme, rewritten on every apply
+const COLOR_SCHEME_FILE: &str = "reskin.conf";
+
+// Where themes keep Kvantum folders, each holding <Name>.kvconfig and <Name>.svg
+const SOURCE_DIRS: [&str; 2] = ["Kvantum", "kvantum"];
+
+pub fn kvantum_dir() -> Result<PathBuf, String> {
+    Ok(config_home()?.join("Kvantum"))
+}
+
+fn is_kvantum_theme(dir: &Path) -> bool {
+    match dir.file_name() {
+        Some(name) => dir.join(format!("{}.kvconfig", name.to_string_lossy())).is_file(),
+        None => false,
+    }
+}
+
+// Every Kvantum theme folder in a theme directory
+pub fn find_kvantum_themes(theme_path: &Path) -> Vec<PathBuf> {
+    let mut themes: Vec<PathBuf> = Vec::new();
+    for dir in SOURCE_DIRS {
+        let mut entries: Vec<PathBuf> = match fs::read_dir(theme_path.join(dir)) {
+            Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+            Err(_) => continue,
+        };
+        entries.sort();
+        for path in entries.into_iter().filter(|path| is_kvantum_theme(path)) {
+            if !themes.iter().any(|t| t.file_name() == path.file_name()) {
+                themes.push(path);
+            }
+        }
+    }
+    themes
+}
+
+// Copy the Kvantum themes into ~/.config/Kvantum, replacing only folders this theme installed before
+pub fn install_kvantum_themes(theme_path: &Path, owned: &[String], progress: &mut Progress) -> Result<Vec<PathBuf>, String> {
+    let dir = kvantum_dir()?;
+    let mut paths = Vec::new();
+    for source in find_kvantum_themes(theme_path) {
+        progress.check_cancelled()?;
+        let name = source.file_name().ok_or("Invalid Kvantum theme path")?;
+        let dest = dir.join(name);
+
+        if dest.exists() {
+            if !owned.contains(&dest.to_string_lossy().to_string()) {
+                return Err(format!("{} already exists and was not installed by this theme", dest.display()));
+            }
+            fs::remove_dir_all(&dest)
+                .map_err(|e| format!("Failed to remove {}: {}", dest.display(), e))?;
+        }
+
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        progress.track_cleanup(dest.clone());
+        copy_dir_with_progress(&source.to_string_lossy(), &dest.to_string_lossy(), progress)
+            .map_err(|e| format!("Failed to install Kvantum theme {}: {}", name.to_string_lossy(), e))?;
+        paths.push(dest);
+    }
+    Ok(paths)
+}
+
+// Kvantum theme names among an install's recorded paths
+pub fn installed_kvantum_themes(paths: &[String]) -> Vec<String> {
+    let dir = match kvantum_dir() {
+        Ok(dir) => dir,
+        Err(_) => return Vec::new(),
+    };
+    paths.iter()
+        .map(Path::new)
+        .filter(|path| path.parent() == Some(dir.as_path()))
+        .filter_map(|path| path.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .collect()
+}
+
+// Themes often ship Foo and FooDark side by side, pick the one matching the scheme
+pub fn pick_kvantum_theme(themes: &[String], dark: bool) -> Option<&String> {
+    themes.iter()
+        .find(|theme| theme.to_lowercase().contains("dark") == dark)
+        .or_else(|| themes.first())
+}
+
+// Set a key inside an ini section, keeping the rest of the file as it was
+fn set_ini_value(path: &Path, section: &str, key: &str, value: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let existing = fs::read_to_string(path).unwrap_or_default();
+    let mut lines: Vec<String> = existing.lines().map(|line| line.to_string()).collect();
+    let header = format!("[{}]", section);
+    let entry = format!("{}={}", key, value);
+
+    match lines.iter().position(|line| line.trim() == header) {
+        Some(start) => {
+            let end = lines[start + 1..].iter()
+                .position(|line| line.trim().starts_with('['))
+                .map_or(lines.len(), |offset| start + 1 + offset);
+            let section: Vec<String> = lines.drain(start + 1..end)
+                .filter(|line| line.split('=').next().map(str::trim) != Some(key))
+                .collect();
+            lines.splice(start + 1..start + 1, std::iter::once(entry).chain(section));
+        }
+        None => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(header);
+            lines.push(entry);
+        }
+    }
+
+    fs::write(path, lines.join("\n") + "\n")
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn get_ini_value(path: &Path, section: &str, key: &str) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let header = format!("[{}]", section);
+    content.lines()
+        .skip_while(|line| line.trim() != header)
+        .skip(1)
+        .take_while(|line| !line.trim().starts_with('['))
+        .filter_map(|line| line.split_once('='))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, v)| v.trim().to_string())
+}
+
+fn kvantum_config() -> Result<PathBuf, String> {
+    Ok(kvantum_dir()?.join("kvantum.kvconfig"))
+}
+
+// Kvantum Manager writes the same key, so both stay in agreement
+pub fn set_kvantum_theme(name: &str) -> Result<(), String> {
+    set_ini_value(&kvantum_config()?, "General", "theme", name)
+}
+
+pub fn kvantum_theme() -> Option<String> {
+    get_ini_value(&kvantum_config().ok()?, "General", "theme")
+}
+
+// The qt5ct/qt6ct configurations that exist or whose tool is installed
+fn qtct_tools() -> Vec<&'static str> {
+    let config = match config_home() {
+        Ok(config) => config,
+        Err(_) => return Vec::new(),
+    };
+    ["qt5ct", "qt6ct"].into_iter()
+        .filter(|tool| config.join(tool).is_dir() || command_exists(tool))
+        .collect()
+}
+
+fn set_qtct_value(key: &str, value: &str) -> Result<(), String> {
+    let config = config_home()?;
+    for tool in qtct_tools() {
+        set_ini_value(&config.join(tool).join(format!("{}.conf", tool)), "Appearance", key, value)?;
+    }
+    Ok(())
+}
+
+fn qt_environment_file() -> Result<PathBuf, String> {
+    Ok(config_home()?.join("environment.d/60-reskin-qt.conf"))
+}
+
+// The qt5ct/qt6ct configs, generated color schemes and environment file, recorded by snapshots
+pub fn qt_config_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if let Ok(config) = config_home() {
+        for tool in ["qt5ct", "qt6ct"] {
+            files.push(config.join(tool).join(format!("{}.conf", tool)));
+            files.push(config.join(tool).join("colors").join(COLOR_SCHEME_FILE));
+        }
+    }
+    files.extend(qt_environment_file());
+    files
+}
+
+// Qt apps only load qt5ct/qt6ct through the platform theme variable, Plasma sets its own.
+// It changes every Qt app of the next session, so it's opt-in
+fn write_qt_environment(kvantum: bool) -> Result<(), String> {
+    if desktop_is(&["KDE"]) || !load_settings().qt_environment {
+        return Ok(());
+    }
+    let content = match qtct_tools().last() {
+        Some(tool) => format!("QT_QPA_PLATFORMTHEME={}\n", tool),
+        // Without qt5ct/qt6ct the Kvantum style can still be forced
+        None if kvantum => "QT_STYLE_OVERRIDE=kvantum\n".to_string(),
+        None => return Err("Neither qt5ct nor qt6ct is installed".to_string()),
+    };
+    let file = qt_environment_file()?;
+    if let Some(env_dir) = file.parent() {
+        fs::create_dir_all(env_dir)
+            .map_err(|e| format!("Failed to create {}: {}", env_dir.display(), e))?;
+    }
+    fs::write(file, content)
+        .map_err(|e| format!("Failed to write Qt environment: {}", e))
+}
+
+fn remove_qt_environment() -> Result<(), String> {
+    let file = qt_environment_file()?;
+    if !file.exists() {
+        return Ok(());
+    }
+    fs::remove_file(&file)
+        .map_err(|e| format!("Failed to remove {}: {}", file.display(), e))
+}
+
+// Use the Kvantum style in qt5ct/qt6ct too, so apps started through them pick the theme up
+pub fn apply_kvantum(name: &str) -> Result<(), String> {
+    set_kvantum_theme(name)?;
+    set_qtct_value("style", "kvantum")?;
+    write_qt_environment(true)
+}
+
+fn rgb(hex: &str) -> Option<(u8, u8, u8)> {
+    parse_accent(hex.get(..7)?)
+}
+
+fn mix((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8), amount: f64) -> (u8, u8, u8) {
+    let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * amount).round() as u8;
+    (channel(r1, r2), channel(g1, g2), channel(b1, b2))
+}
+
+// qt5ct stores colors as #aarrggbb
+fn argb(color: (u8, u8, u8)) -> String {
+    format!("#ff{}", &accent_hex(color)[1..])
+}
+
+// One color per QPalette role, in the order qt5ct/qt6ct list them
+fn palette_roles(named: &std::collections::BTreeMap<String, String>) -> Result<[(u8, u8, u8); 21], String> {
+    let color = |name: &str| named.get(name).and_then(|value| rgb(value))
+        .ok_or_else(|| format!("The GTK palette has no {}", name));
+    let window_bg = color("window_bg_color")?;
+    let window_fg = color("window_fg_color")?;
+    let view_bg = color("view_bg_color")?;
+    let view_fg = color("view_fg_color")?;
+    let accent_bg = color("accent_bg_color")?;
+    let accent_fg = color("accent_fg_color").unwrap_or((255, 255, 255));
+    let accent = color("accent_color").unwrap_or(accent_bg);
+    let tooltip_bg = color("popover_bg_color").unwrap_or(view_bg);
+    let tooltip_fg = color("popover_fg_color").unwrap_or(view_fg);
+    let (white, black) = ((255, 255, 255), (0, 0, 0));
+
+    Ok([
+        window_fg,                      // WindowText
+        window_bg,                      // Button
+        mix(window_bg, white, 0.4),     // Light
+        mix(window_bg, white, 0.2),     // Midlight
+        mix(window_bg, black, 0.4),     // Dark
+        mix(window_bg, black, 0.2),     // Mid
+        view_fg,                        // Text
+        white,                          // BrightText
+        window_fg,                      // ButtonText
+        view_bg,                        // Base
+        window_bg,                      // Window
+        black,                          // Shadow
+        accent_bg,                      // Highlight
+        accent_fg,                      // HighlightedText
+        accent,                         // Link
+        mix(accent, window_fg, 0.3),    // LinkVisited
+        mix(view_bg, view_fg, 0.04),    // AlternateBase
+        window_bg,                      // NoRole
+        tooltip_bg,                     // ToolTipBase
+        tooltip_fg,                     // ToolTipText
+        mix(view_fg, view_bg, 0.5),     // PlaceholderText
+    ])
+}
+
+// Text roles fade towards their background when disabled
+const DISABLED_TEXT_ROLES: [(usize, usize); 5] = [(0, 10), (6, 9), (8, 1), (13, 12), (20, 9)];
+
+fn color_scheme(roles: &[(u8, u8, u8); 21]) -> String {
+    let line = |roles: &[(u8, u8, u8)]| roles.iter().map(|c| argb(*c)).collect::<Vec<_>>().join(", ");
+    let mut disabled = *roles;
+    for (text, background) in DISABLED_TEXT_ROLES {
+        disabled[text] = mix(roles[text], roles[background], 0.5);
+    }
+    format!(
+        "[ColorScheme]\nactive_colors={}\ndisabled_colors={}\ninactive_colors={}\n",
+        line(roles), line(&disabled), line(roles)
+    )
+}
+
+// Turn the GTK theme's palette into a qt5ct/qt6ct color scheme and select it
+pub fn apply_qt_colors(theme_name: &str, dark: bool) -> Result<(), String> {
+    let theme_dir = find_theme_dir(theme_name)
+        .ok_or_else(|| format!("{} is not installed", theme_name))?;
+    let palette = read_palette(&theme_dir);
+    let variant = if dark { palette.dark.or(palette.light) } else { palette.light.or(palette.dark) };
+    let variant = variant.ok_or_else(|| format!("{} defines no GTK colors", theme_name))?;
+    let scheme = color_scheme(&palette_roles(&variant.named)?);
+
+    let tools = qtct_tools();
+    if tools.is_empty() {
+        return Err("Neither qt5ct nor qt6ct is installed".to_string());
+    }
+    let config = config_home()?;
+    for tool in tools {
+        let colors_dir = config.join(tool).join("colors");
+        fs::create_dir_all(&colors_dir)
+            .map_err(|e| format!("Failed to create {}: {}", colors_dir.display(), e))?;
+        let path = colors_dir.join(COLOR_SCHEME_FILE);
+        fs::write(&path, &scheme)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        let conf = config.join(tool).join(format!("{}.conf", tool));
+        set_ini_value(&conf, "Appearance", "color_scheme_path", &path.to_string_lossy())?;
+        set_ini_value(&conf, "Appearance", "custom_palette", "true")?;
+    }
+    write_qt_environment(false)
+}
+
+// Go back to the style's own palette and stop loading qt5ct/qt6ct
+fn revert_qt_colors() -> Result<(), String> {
+    set_qtct_value("custom_palette", "false")?;
+    remove_qt_environment()
+}
+
+#[tauri::command]
+pub fn get_qt_color_schemes() -> Result<bool, String> {
+    Ok(load_settings().qt_color_schemes)
+}
+
+#[tauri::command]
+pub fn set_qt_color_schemes(enabled: bool) -> Result<(), String> {
+    let mut settings = load_settings();
+    settings.qt_color_schemes = enabled;
+    save_settings(&settings)?;
+
+    if !enabled {
+        revert_qt_colors()?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_qt_environment() -> Result<bool, String> {
+    Ok(load_settings().qt_environment)
+}
+
+#[tauri::command]
+pub fn set_qt_environment(enabled: bool) -> Result<(), String> {
+    let mut settings = load_settings();
+    settings.qt_environment = enabled;
+    save_settings(&settings)?;
+
+    if !enabled {
+        remove_qt_environment()?;
+    }
+    Ok(())
+}
@@ -1,16 +1,14 @@
 use std::fs;
 use std::time::SystemTime;
+use crate::paths::reskin_config_dir;
 use crate::types::RecentTheme;
 
 
 #[tauri::command]
 pub fn get_recent_themes() -> Result<Vec<RecentTheme>, String> {
-    let home_dir = std::env::var("HOME")
-        .map_err(|_| "Failed to get HOME directory".to_string())?;
+    let recent_file = reskin_config_dir()?.join("recent.json");
     
-    let recent_file = format!("{}/.config/reskin/recent.json", home_dir);
-    
-    if !std::path::Path::new(&recent_file).exists() {
+    if !recent_file.exists() {
         return Ok(Vec::new());
     }
     
@@ -25,17 +23,14 @@ pub fn get_recent_themes() -> Result<Vec<RecentTheme>, String> {
 
 #[tauri::command]
 pub fn add_recent_theme(theme_name: String, author: String, description: String) -> Result<(), String> {
-    let home_dir = std::env::var("HOME")
-        .map_err(|_| "Failed to get HOME directory".to_string())?;
-    
-    let config_dir = format!("{}/.config/reskin", home_dir);
+    let config_dir = reskin_config_dir()?;
     fs::create_dir_all(&config_dir)
         .map_err(|e| format!("Failed to create config directory: {}", e))?;
     
-    let recent_file = format!("{}/recent.json", config_dir);
+    let recent_file = config_dir.join("recent.json");
     
     // Load existing themes
-    let mut themes: Vec<RecentTheme> = if std::path::Path::new(&recent_file).exists() {
+    let mut themes: Vec<RecentTheme> = if recent_file.exists() {
         let content = fs::read_to_string(&recent_file).unwrap_or_default();
         serde_json::from_str(&content).unwrap_or_else(|_| Vec::new())
     } else {
@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::paths::reskin_config_dir;
+use crate::types::{InstallScope, InstalledTheme, ThemeManifest};
+
+// System installs are recorded by the privileged helper, the file is world-readable
+pub const SYSTEM_REGISTRY_FILE: &str = "/var/lib/reskin/installed.json";
+
+fn registry_file(scope: InstallScope) -> Result<PathBuf, String> {
+    match scope {
+        InstallScope::User => Ok(reskin_config_dir()?.join("installed.json")),
+        InstallScope::System => Ok(PathBuf::from(SYSTEM_REGISTRY_FILE)),
+    }
+}
+
+// Load the themes reskin installed for the given scope
+pub fn load_registry(scope: InstallScope) -> Vec<InstalledTheme> {
+    registry_file(scope)
+        .ok()
+        .and_then(|file| fs::read_to_string(file).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_user_registry(themes: &[InstalledTheme]) -> Result<(), String> {
+    let config_dir = reskin_config_dir()?;
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+
+    let json = serde_json::to_string_pretty(themes)
+        .map_err(|e| format!("Failed to serialize registry: {}", e))?;
+
+    fs::write(config_dir.join("installed.json"), json)
+        .map_err(|e| format!("Failed to write registry: {}", e))
+}
+
+pub fn find_installed(theme_name: &str, scope: InstallScope) -> Option<InstalledTheme> {
+    load_registry(scope).into_iter().find(|t| t.name == theme_name)
+}
+
+// The reskin.json an installed theme was bundled with, registry entries only keep names and paths
+pub fn installed_manifest(theme: &InstalledTheme) -> Option<ThemeManifest> {
+    theme.paths.iter()
+        .filter_map(|path| fs::read_to_string(Path::new(path).join("reskin.json")).ok())
+        .find_map(|json| serde_json::from_str(&json).ok())
+}
+
+// Record a user install, replacing any previous entry with the same name
+pub fn record_user_install(theme_name: &str, components: &[&str], paths: &[PathBuf]) -> Result<(), String> {
+    let mut themes = load_registry(InstallScope::User);
+    themes.retain(|t| t.name != theme_name);
+
+    themes.push(InstalledTheme {
+        name: theme_name.to_string(),
+        scope: InstallScope::User,
+        components: components.iter().map(|c| c.to_string()).collect(),
+        paths: paths.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+        installed_at: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+    });
+
+    save_user_registry(&themes)
+}
+
+pub fn remove_user_install(theme_name: &str) -> Result<(), String> {
+    let mut themes = load_registry(InstallScope::User);
+    themes.retain(|t| t.name != theme_name);
+    save_user_registry(&themes)
+}
+
+#[tauri::command]
+pub fn get_installed_themes() -> Result<Vec<InstalledTheme>, String> {
+    let mut themes = load_registry(InstallScope::User);
+    themes.extend(load_registry(InstallScope::System));
+    Ok(themes)
+}
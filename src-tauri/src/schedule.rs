@@ -0,0 +1,388 @@
+// Light/dark schedule: a headless `reskin --schedule` process switches themes at fixed times or at sunrise and sunset
+use std::f64::consts::PI;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+use crate::apply::apply_components;
+use crate::desktop::{run, summarize};
+use crate::paths::{config_home, reskin_config_dir};
+use crate::settings::{load_settings, save_settings};
+use crate::types::{ColorScheme, ScheduleConfig, ScheduleMode, ScheduleStatus};
+
+pub const SCHEDULE_ARG: &str = "--schedule";
+const UNIT_NAME: &str = "reskin-schedule.service";
+const DAY: i64 = 86400;
+// Longest sleep between checks, so suspend and clock changes are noticed
+const CHECK_INTERVAL: i64 = 60;
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+// Local UTC offset in seconds, asked again every time so DST changes are picked up
+fn local_offset() -> i64 {
+    let output = Command::new("date").arg("+%z").output().ok();
+    let offset = output.map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string()).unwrap_or_default();
+    let (sign, digits) = match (offset.strip_prefix('+'), offset.strip_prefix('-')) {
+        (Some(digits), _) => (1, digits),
+        (_, Some(digits)) => (-1, digits),
+        _ => return 0,
+    };
+    let hours: i64 = digits.get(0..2).and_then(|h| h.parse().ok()).unwrap_or(0);
+    let minutes: i64 = digits.get(2..4).and_then(|m| m.parse().ok()).unwrap_or(0);
+    sign * (hours * 3600 + minutes * 60)
+}
+
+// "07:30" as seconds after midnight
+fn parse_time(time: &str) -> Option<i64> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    let (hours, minutes): (i64, i64) = (hours.parse().ok()?, minutes.parse().ok()?);
+    ((0..24).contains(&hours) && (0..60).contains(&minutes)).then_some(hours * 3600 + minutes * 60)
+}
+
+// Sunrise and sunset as unix timestamps for a day counted from the epoch, None during polar day or night.
+// The sunrise equation, accurate to a minute or two, which is plenty for a theme switch
+fn sun_times(day: i64, latitude: f64, longitude: f64) -> Option<(i64, i64)> {
+    let rad = PI / 180.0;
+    // Days since 2000-01-01 12:00 UTC, shifted to local mean solar noon
+    let n = (day - 10957) as f64;
+    let mean_noon = n - longitude / 360.0;
+    let anomaly = (357.5291 + 0.98560028 * mean_noon).rem_euclid(360.0);
+    let center = 1.9148 * (anomaly * rad).sin() + 0.02 * (2.0 * anomaly * rad).sin() + 0.0003 * (3.0 * anomaly * rad).sin();
+    let ecliptic = (anomaly + center + 180.0 + 102.9372).rem_euclid(360.0);
+    let transit = 2451545.0 + mean_noon + 0.0053 * (anomaly * rad).sin() - 0.0069 * (2.0 * ecliptic * rad).sin();
+    let declination = ((ecliptic * rad).sin() * (23.4397 * rad).sin()).asin();
+
+    let cos_hour_angle = ((-0.833 * rad).sin() - (latitude * rad).sin() * declination.sin())
+        / ((latitude * rad).cos() * declination.cos());
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return None;
+    }
+    let hour_angle = cos_hour_angle.acos() / rad;
+
+    let to_unix = |julian: f64| ((julian - 2440587.5) * DAY as f64).round() as i64;
+    Some((to_unix(transit - hour_angle / 360.0), to_unix(transit + hour_angle / 360.0)))
+}
+
+// When light and dark start on a local day, the day given as local days since the epoch
+fn switch_times(config: &ScheduleConfig, day: i64, offset: i64) -> Option<(i64, i64)> {
+    match config.mode {
+        ScheduleMode::Fixed => {
+            let midnight = day * DAY - offset;
+            Some((midnight + parse_time(&config.light_time)?, midnight + parse_time(&config.dark_time)?))
+        }
+        ScheduleMode::Sun => sun_times(day, config.latitude, config.longitude),
+    }
+}
+
+// The side of the schedule in effect at `time` and the next switch after it
+fn plan(config: &ScheduleConfig, time: i64, offset: i64) -> (Option<ColorScheme>, Option<(i64, ColorScheme)>) {
+    let today = (time + offset).div_euclid(DAY);
+
+    let mut events: Vec<(i64, ColorScheme)> = (today - 1..=today + 2)
+        .filter_map(|day| switch_times(config, day, offset))
+        .flat_map(|(light, dark)| [(light, ColorScheme::Light), (dark, ColorScheme::Dark)])
+        .collect();
+    events.sort_by_key(|(at, _)| *at);
+
+    let active = events.iter().rev().find(|(at, _)| *at <= time).map(|(_, scheme)| *scheme);
+    let next = events.into_iter().find(|(at, _)| *at > time);
+    (active, next)
+}
+
+fn theme_for(config: &ScheduleConfig, scheme: ColorScheme) -> &str {
+    match scheme {
+        ColorScheme::Dark => &config.dark_theme,
+        _ => &config.light_theme,
+    }
+}
+
+fn validate(config: &ScheduleConfig) -> Result<(), String> {
+    if !config.enabled {
+        return Ok(());
+    }
+    if config.light_theme.trim().is_empty() || config.dark_theme.trim().is_empty() {
+        return Err("Choose both a light and a dark theme".to_string());
+    }
+    match config.mode {
+        ScheduleMode::Fixed => {
+            for time in [&config.light_time, &config.dark_time] {
+                parse_time(time).ok_or(format!("Invalid time: {}, use HH:MM", time))?;
+            }
+        }
+        ScheduleMode::Sun => {
+            if !(-90.0..=90.0).contains(&config.latitude) || !(-180.0..=180.0).contains(&config.longitude) {
+                return Err("Latitude must be within ±90 and longitude within ±180".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+// The binary to start, an AppImage has to be started through the image rather than its mount point
+fn executable() -> Result<String, String> {
+    if let Ok(appimage) = std::env::var("APPIMAGE") {
+        return Ok(appimage);
+    }
+    std::env::current_exe()
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(|e| format!("Failed to find the reskin executable: {}", e))
+}
+
+fn unit_file() -> Result<PathBuf, String> {
+    Ok(config_home()?.join("systemd/user").join(UNIT_NAME))
+}
+
+fn autostart_file() -> Result<PathBuf, String> {
+    Ok(config_home()?.join("autostart/reskin-schedule.desktop"))
+}
+
+fn pid_file() -> Result<PathBuf, String> {
+    Ok(reskin_config_dir()?.join("schedule.pid"))
+}
+
+// Another scheduler already running, e.g. started by both autostart and a configure call
+fn scheduler_running() -> bool {
+    pid_file()
+        .ok()
+        .and_then(|file| fs::read_to_string(file).ok())
+        .and_then(|pid| pid.trim().parse::<u32>().ok())
+        .filter(|pid| *pid != std::process::id())
+        .and_then(|pid| fs::read(format!("/proc/{}/cmdline", pid)).ok())
+        .is_some_and(|cmdline| cmdline.split(|b| *b == 0).any(|arg| arg == SCHEDULE_ARG.as_bytes()))
+}
+
+fn write_file(path: &PathBuf, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+// Prefer a systemd user unit tied to the graphical session, fall back to an autostart entry.
+// Sessions that don't start graphical-session.target (most sway and Hyprland setups) would never run the unit
+fn install_runner() -> Result<String, String> {
+    let exe = executable()?;
+    let session_target = run("systemctl", &["--user", "is-active", "--quiet", "graphical-session.target"]);
+    let unit = format!(
+        "[Unit]\nDescription=reskin light/dark theme schedule\nPartOf=graphical-session.target\nAfter=graphical-session.target\n\n[Service]\nExecStart=\"{}\" {}\nRestart=on-failure\n\n[Install]\nWantedBy=graphical-session.target\n",
+        exe, SCHEDULE_ARG
+    );
+    let systemd = session_target
+        .and_then(|_| write_file(&unit_file()?, &unit))
+        .and_then(|_| run("systemctl", &["--user", "daemon-reload"]))
+        .and_then(|_| run("systemctl", &["--user", "enable", "--now", UNIT_NAME]));
+    if systemd.is_ok() {
+        let _ = fs::remove_file(autostart_file()?);
+        return Ok("systemd".to_string());
+    }
+
+    if unit_file()?.exists() {
+        let _ = run("systemctl", &["--user", "disable", UNIT_NAME]);
+        let _ = fs::remove_file(unit_file()?);
+    }
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=reskin schedule\nExec=\"{}\" {}\nNoDisplay=true\nX-GNOME-Autostart-enabled=true\n",
+        exe, SCHEDULE_ARG
+    );
+    write_file(&autostart_file()?, &entry)?;
+    if !scheduler_running() {
+        Command::new(&exe)
+            .arg(SCHEDULE_ARG)
+            .spawn()
+            .map_err(|e| format!("Failed to start the scheduler: {}", e))?;
+    }
+    Ok("autostart".to_string())
+}
+
+fn uninstall_runner() -> Result<(), String> {
+    if unit_file()?.exists() {
+        let _ = run("systemctl", &["--user", "disable", "--now", UNIT_NAME]);
+        fs::remove_file(unit_file()?)
+            .map_err(|e| format!("Failed to remove {}: {}", UNIT_NAME, e))?;
+        let _ = run("systemctl", &["--user", "daemon-reload"]);
+    }
+    let autostart = autostart_file()?;
+    if autostart.exists() {
+        fs::remove_file(&autostart)
+            .map_err(|e| format!("Failed to remove {}: {}", autostart.display(), e))?;
+    }
+    // An autostarted scheduler notices it was disabled on its next check
+    Ok(())
+}
+
+fn installed_runner() -> Option<String> {
+    if unit_file().ok()?.exists() {
+        Some("systemd".to_string())
+    } else if autostart_file().ok()?.exists() {
+        Some("autostart".to_string())
+    } else {
+        None
+    }
+}
+
+fn status(config: &ScheduleConfig) -> ScheduleStatus {
+    if !config.enabled {
+        return ScheduleStatus { active: None, next_switch: None, next_theme: None, runner: installed_runner() };
+    }
+    let (active, next) = plan(config, now(), local_offset());
+    ScheduleStatus {
+        active,
+        next_switch: next.map(|(at, _)| at as u64),
+        next_theme: next.map(|(_, scheme)| theme_for(config, scheme).to_string()),
+        runner: installed_runner(),
+    }
+}
+
+// Entry point of `reskin --schedule`, returns once the schedule is disabled
+pub fn run_scheduler() {
+    if scheduler_running() {
+        eprintln!("reskin scheduler is already running");
+        return;
+    }
+    if let Ok(file) = pid_file() {
+        let _ = write_file(&file, &std::process::id().to_string());
+    }
+
+    let mut applied: Option<(ColorScheme, String)> = None;
+    loop {
+        let config = load_settings().schedule;
+        if !config.enabled {
+            break;
+        }
+
+        // Only switch on changes, so a theme the user picks by hand stays until the next switch
+        let time = now();
+        let (active, next) = plan(&config, time, local_offset());
+        if let Some(scheme) = active {
+            let theme = theme_for(&config, scheme).to_string();
+            if applied.as_ref() != Some(&(scheme, theme.clone())) {
+                match summarize(&apply_components(&theme, None, false), "Failed to apply any theme components") {
+                    Ok(message) => eprintln!("Switched to {}\n{}", theme, message),
+                    Err(e) => eprintln!("Failed to switch to {}: {}", theme, e),
+                }
+                applied = Some((scheme, theme));
+            }
+        }
+
+        let wait = next.map_or(CHECK_INTERVAL, |(at, _)| (at - time).clamp(1, CHECK_INTERVAL));
+        std::thread::sleep(Duration::from_secs(wait as u64));
+    }
+
+    if let Ok(file) = pid_file() {
+        let _ = fs::remove_file(file);
+    }
+}
+
+#[tauri::command]
+pub fn get_schedule() -> Result<ScheduleConfig, String> {
+    Ok(load_settings().schedule)
+}
+
+#[tauri::command]
+pub fn configure_schedule(config: ScheduleConfig) -> Result<ScheduleStatus, String> {
+    validate(&config)?;
+    let mut settings = load_settings();
+    settings.schedule = config.clone();
+    save_settings(&settings)?;
+
+    if config.enabled {
+        install_runner()?;
+    } else {
+        uninstall_runner()?;
+    }
+    Ok(status(&config))
+}
+
+#[tauri::command]
+pub fn get_schedule_status() -> Result<ScheduleStatus, String> {
+    Ok(status(&load_settings().schedule))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-06-21 as days since the epoch
+    const SOLSTICE: i64 = 19895;
+
+    fn fixed(light: &str, dark: &str) -> ScheduleConfig {
+        ScheduleConfig {
+            enabled: true,
+            light_time: light.to_string(),
+            dark_time: dark.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(parse_time("07:30"), Some(7 * 3600 + 30 * 60));
+        assert_eq!(parse_time(" 0:00 "), Some(0));
+        assert_eq!(parse_time("23:59"), Some(23 * 3600 + 59 * 60));
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("12:60"), None);
+        assert_eq!(parse_time("-1:30"), None);
+        assert_eq!(parse_time("1230"), None);
+        assert_eq!(parse_time("ab:cd"), None);
+    }
+
+    #[test]
+    fn sun_times_match_almanac() {
+        // London: sunrise 03:43 UTC, sunset 20:21 UTC
+        let (sunrise, sunset) = sun_times(SOLSTICE, 51.5074, -0.1278).unwrap();
+        let midnight = SOLSTICE * DAY;
+        assert!((sunrise - (midnight + 3 * 3600 + 43 * 60)).abs() < 300, "sunrise {}", sunrise - midnight);
+        assert!((sunset - (midnight + 20 * 3600 + 21 * 60)).abs() < 300, "sunset {}", sunset - midnight);
+
+        // The equator gets about twelve hours all year
+        let (sunrise, sunset) = sun_times(SOLSTICE, 0.0, 0.0).unwrap();
+        assert!((sunset - sunrise - 12 * 3600).abs() < 15 * 60);
+    }
+
+    #[test]
+    fn sun_times_polar_day_and_night() {
+        assert_eq!(sun_times(SOLSTICE, 78.2, 15.6), None);
+        assert_eq!(sun_times(SOLSTICE, -78.2, 15.6), None);
+    }
+
+    #[test]
+    fn plans_fixed_schedule() {
+        let config = fixed("07:00", "19:00");
+        let midnight = SOLSTICE * DAY;
+
+        let (active, next) = plan(&config, midnight + 12 * 3600, 0);
+        assert!(active == Some(ColorScheme::Light));
+        assert!(next == Some((midnight + 19 * 3600, ColorScheme::Dark)));
+
+        // Before sunrise the previous evening's dark switch is in effect
+        let (active, next) = plan(&config, midnight + 3 * 3600, 0);
+        assert!(active == Some(ColorScheme::Dark));
+        assert!(next == Some((midnight + 7 * 3600, ColorScheme::Light)));
+
+        // A switch is in effect from its own second on
+        let (active, _) = plan(&config, midnight + 19 * 3600, 0);
+        assert!(active == Some(ColorScheme::Dark));
+    }
+
+    #[test]
+    fn plans_in_local_time() {
+        let config = fixed("07:00", "19:00");
+        let midnight = SOLSTICE * DAY;
+        // UTC+2, 07:00 local is 05:00 UTC
+        let (active, next) = plan(&config, midnight + 4 * 3600, 2 * 3600);
+        assert!(active == Some(ColorScheme::Dark));
+        assert!(next == Some((midnight + 5 * 3600, ColorScheme::Light)));
+    }
+
+    #[test]
+    fn plans_nothing_without_switches() {
+        let config = ScheduleConfig { mode: ScheduleMode::Sun, latitude: 78.2, longitude: 15.6, ..fixed("", "") };
+        assert!(plan(&config, SOLSTICE * DAY, 0) == (None, None));
+    }
+}
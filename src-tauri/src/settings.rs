@@ -0,0 +1,43 @@
+use std::fs;
+use crate::paths::{reskin_config_dir, themes_dir_for};
+use crate::types::{InstallLocation, ReskinSettings};
+
+// Load settings from ~/.config/reskin/settings.json, falling back to defaults
+pub fn load_settings() -> ReskinSettings {
+    let settings_file = match reskin_config_dir() {
+        Ok(dir) => dir.join("settings.json"),
+        Err(_) => return ReskinSettings::default(),
+    };
+
+    fs::read_to_string(settings_file)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_settings(settings: &ReskinSettings) -> Result<(), String> {
+    let config_dir = reskin_config_dir()?;
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    fs::write(config_dir.join("settings.json"), json)
+        .map_err(|e| format!("Failed to write settings: {}", e))
+}
+
+#[tauri::command]
+pub fn get_install_location() -> Result<InstallLocation, String> {
+    Ok(load_settings().install_location)
+}
+
+#[tauri::command]
+pub fn set_install_location(location: InstallLocation) -> Result<String, String> {
+    let mut settings = load_settings();
+    settings.install_location = location;
+    save_settings(&settings)?;
+
+    // Return the resolved themes directory so the UI can show it
+    Ok(themes_dir_for(location)?.to_string_lossy().to_string())
+}
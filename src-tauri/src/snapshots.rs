@@ -0,0 +1,203 @@
+// Appearance snapshots taken before apply, so the previous look can be put back
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use crate::cursors::cursor_fallback_files;
+use crate::desktop::{detect_backend, report, summarize, ApplyError, DesktopBackend};
+use crate::flatpak::override_files;
+use crate::libadwaita::{apply_libadwaita, linked_theme, revert_libadwaita, LIBADWAITA};
+use crate::paths::reskin_config_dir;
+use crate::qt::{kvantum_theme, qt_config_files, set_kvantum_theme, KVANTUM};
+use crate::settings::load_settings;
+use crate::types::{ApplyReport, SnapshotEntry};
+
+const MAX_SNAPSHOTS: usize = 30;
+
+fn snapshots_file() -> Result<PathBuf, String> {
+    Ok(reskin_config_dir()?.join("snapshots.json"))
+}
+
+// Oldest first
+fn load_snapshots() -> Vec<SnapshotEntry> {
+    snapshots_file()
+        .ok()
+        .and_then(|file| fs::read_to_string(file).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_snapshots(snapshots: &[SnapshotEntry]) -> Result<(), String> {
+    let config_dir = reskin_config_dir()?;
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+
+    let json = serde_json::to_string_pretty(snapshots)
+        .map_err(|e| format!("Failed to serialize snapshots: {}", e))?;
+
+    fs::write(snapshots_file()?, json)
+        .map_err(|e| format!("Failed to write snapshots: {}", e))
+}
+
+// Config files reskin edits directly, by path, None for the ones that don't exist.
+// Files that can't be read are left out so restore never deletes them
+fn read_files() -> BTreeMap<String, Option<String>> {
+    let paths = override_files().into_iter()
+        .chain(cursor_fallback_files())
+        .chain(qt_config_files());
+    let mut files = BTreeMap::new();
+    for path in paths {
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(_) => continue,
+        };
+        files.insert(path.to_string_lossy().to_string(), content);
+    }
+    files
+}
+
+fn restore_files(files: &BTreeMap<String, Option<String>>) -> Result<(), String> {
+    for (path, content) in files {
+        let path = PathBuf::from(path);
+        match content {
+            Some(content) => {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir)
+                        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+                }
+                fs::write(&path, content)
+                    .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            }
+            None if path.exists() => {
+                fs::remove_file(&path)
+                    .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+// Record the backend's current settings before they get changed
+pub fn take_snapshot(backend: &dyn DesktopBackend, label: &str) -> Result<(), String> {
+    let mut snapshots = load_snapshots();
+    let mut snapshot = backend.snapshot();
+    if let Some(theme) = linked_theme() {
+        snapshot.values.insert(LIBADWAITA.to_string(), theme);
+    }
+    if let Some(theme) = kvantum_theme() {
+        snapshot.values.insert(KVANTUM.to_string(), theme);
+    }
+    snapshot.files = read_files();
+
+    // Applying twice in a row shouldn't fill the history with identical entries
+    if snapshots.last().is_some_and(|last| {
+        last.snapshot.desktop == snapshot.desktop
+            && last.snapshot.values == snapshot.values
+            && last.snapshot.files == snapshot.files
+    }) {
+        return Ok(());
+    }
+
+    snapshots.push(SnapshotEntry {
+        id: snapshots.iter().map(|s| s.id).max().map_or(1, |id| id + 1),
+        label: label.to_string(),
+        created_at: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        snapshot,
+    });
+    let excess = snapshots.len().saturating_sub(MAX_SNAPSHOTS);
+    snapshots.drain(..excess);
+
+    save_snapshots(&snapshots)
+}
+
+fn restore(entry: &SnapshotEntry) -> Result<ApplyReport, String> {
+    let backend = detect_backend();
+    if backend.name() != entry.snapshot.desktop {
+        return Err(format!(
+            "Snapshot was taken on {}, the current desktop is {}",
+            entry.snapshot.desktop, backend.name()
+        ));
+    }
+
+    let mut reports = backend.restore(&entry.snapshot);
+    if load_settings().libadwaita_theming {
+        let result = match entry.snapshot.values.get(LIBADWAITA) {
+            Some(theme) => apply_libadwaita(theme),
+            None => revert_libadwaita().map(|_| ()),
+        };
+        reports.push(report("libadwaita", result.map_err(ApplyError::from)));
+    }
+    if let Some(theme) = entry.snapshot.values.get(KVANTUM) {
+        reports.push(report("Kvantum theme", set_kvantum_theme(theme).map_err(ApplyError::from)));
+    }
+    // Snapshots from before files were recorded have none
+    if !entry.snapshot.files.is_empty() {
+        reports.push(report("Config files", restore_files(&entry.snapshot.files).map_err(ApplyError::from)));
+    }
+    let message = summarize(&reports, "Failed to restore any settings")?;
+    Ok(ApplyReport { message, components: reports })
+}
+
+// Newest first
+#[tauri::command]
+pub fn list_snapshots() -> Result<Vec<SnapshotEntry>, String> {
+    let mut snapshots = load_snapshots();
+    snapshots.reverse();
+    Ok(snapshots)
+}
+
+// The restore itself is snapshotted so it can be undone in turn
+#[tauri::command]
+pub fn restore_snapshot(id: u64) -> Result<ApplyReport, String> {
+    let entry = load_snapshots().into_iter()
+        .find(|s| s.id == id)
+        .ok_or_else(|| format!("Snapshot {} not found", id))?;
+
+    take_snapshot(detect_backend().as_ref(), &format!("Before restoring \"{}\"", entry.label))?;
+    restore(&entry)
+}
+
+// Put back the newest snapshot and drop it from the history
+#[tauri::command]
+pub fn undo_last_apply() -> Result<ApplyReport, String> {
+    let mut snapshots = load_snapshots();
+    let entry = snapshots.pop().ok_or("Nothing to undo")?;
+
+    let report = restore(&entry)?;
+    save_snapshots(&snapshots)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_files_writes_and_removes() {
+        let dir = std::env::temp_dir().join(format!("reskin-snapshots-{}", std::process::id()));
+        let kept = dir.join("environment.d/60-reskin-cursor.conf");
+        let absent = dir.join(".Xresources");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&absent, "Xcursor.theme: Bibata\n").unwrap();
+
+        let files = BTreeMap::from([
+            (kept.to_string_lossy().to_string(), Some("XCURSOR_THEME=Adwaita\n".to_string())),
+            (absent.to_string_lossy().to_string(), None),
+            (dir.join("never-existed").to_string_lossy().to_string(), None),
+        ]);
+        let result = restore_files(&files);
+        let kept_content = fs::read_to_string(&kept).ok();
+        let absent_exists = absent.exists();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(result.is_ok());
+        assert_eq!(kept_content.as_deref(), Some("XCURSOR_THEME=Adwaita\n"));
+        assert!(!absent_exists);
+    }
+}
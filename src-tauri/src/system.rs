@@ -0,0 +1,41 @@
+// Unprivileged side of system-wide installs, everything privileged goes through the helper
+use std::path::Path;
+use std::process::Command;
+
+// Installed by the deb/rpm packages, must match the path in the polkit policy
+pub const HELPER_PATH: &str = "/usr/lib/reskin/reskin-helper";
+
+fn run_helper(args: &[&str]) -> Result<String, String> {
+    if !Path::new(HELPER_PATH).exists() {
+        return Err(format!(
+            "System-wide installs need the reskin helper at {}. Install reskin from a package to enable them.",
+            HELPER_PATH
+        ));
+    }
+
+    let output = Command::new("pkexec")
+        .arg(HELPER_PATH)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute pkexec: {}", e))?;
+
+    // pkexec uses 126 when the dialog was dismissed and 127 when authorization failed
+    match output.status.code() {
+        Some(0) => Ok(String::from_utf8_lossy(&output.stdout).trim().to_string()),
+        Some(126) => Err("Authentication was cancelled".to_string()),
+        Some(127) => Err("Not authorized to install themes system-wide".to_string()),
+        _ => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+    }
+}
+
+// Copy the given components of a staged theme into /usr/share
+pub fn install_system(theme_path: &Path, theme_name: &str, components: &[&str]) -> Result<String, String> {
+    let theme_path = theme_path.to_string_lossy();
+    let mut args = vec!["install", theme_path.as_ref(), theme_name];
+    args.extend_from_slice(components);
+    run_helper(&args)
+}
+
+pub fn uninstall_system(theme_name: &str) -> Result<String, String> {
+    run_helper(&["uninstall", theme_name])
+}
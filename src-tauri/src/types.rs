@@ -8,6 +8,30 @@ pub struct ThemeManifest {
     pub version: String,
     pub tags: String,
     pub license: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fonts: Option<ThemeFonts>, // Fonts the theme wants for the UI, documents and monospace
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<ThemeDependency>, // Themes installed before this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<ThemeVariant>, // Color scheme each theme directory is meant for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent_color: Option<String>, // "#3584e4" or a GNOME accent name such as "teal"
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorScheme {
+    Default, // No preference, apps pick their own
+    Light,
+    Dark,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ThemeVariant {
+    pub name: String, // Theme directory, e.g. "Nordic-Darker"
+    pub color_scheme: ColorScheme,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent_color: Option<String>, // Overrides the manifest's accent for this variant
 }
 #[derive(Serialize, Deserialize, Clone)]
 pub struct BundleRequest {
@@ -15,6 +39,8 @@ pub struct BundleRequest {
     pub output_path: String,
     pub assets: Vec<String>,
     pub theme_directory: Option<String>, // Directory where theme files are located
+    #[serde(default)]
+    pub generate_preview: bool, // Render preview.png from the theme directory if none is bundled
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -23,4 +49,313 @@ pub struct RecentTheme {
     pub author: String,
     pub description: String,
     pub installed_at: u64, // Unix timestamp
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallLocation {
+    #[default]
+    Default, // ~/.themes and $XDG_DATA_HOME/icons, where reskin always installed
+    Legacy,  // ~/.themes and ~/.icons
+    Xdg,     // $XDG_DATA_HOME/themes and $XDG_DATA_HOME/icons
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ReskinSettings {
+    #[serde(default)]
+    pub install_location: InstallLocation,
+    #[serde(default)]
+    pub flatpak_integration: bool, // Write Flatpak overrides after install and apply
+    #[serde(default)]
+    pub libadwaita_theming: bool, // Link the theme's gtk-4.0 files into ~/.config/gtk-4.0 on apply
+    #[serde(default)]
+    pub qt_color_schemes: bool, // Generate qt5ct/qt6ct color schemes from the GTK palette on apply
+    #[serde(default)]
+    pub qt_environment: bool, // Write QT_QPA_PLATFORMTHEME to environment.d on apply
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleMode {
+    #[default]
+    Fixed, // Switch at light_time and dark_time
+    Sun,   // Switch at sunrise and sunset for latitude/longitude
+}
+
+// Light/dark switching, run headless by `reskin --schedule`
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ScheduleConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub light_theme: String, // Theme or variant name used during the day
+    #[serde(default)]
+    pub dark_theme: String,
+    #[serde(default)]
+    pub mode: ScheduleMode,
+    #[serde(default)]
+    pub light_time: String, // Local "HH:MM"
+    #[serde(default)]
+    pub dark_time: String,
+    #[serde(default)]
+    pub latitude: f64, // Degrees, north positive
+    #[serde(default)]
+    pub longitude: f64, // Degrees, east positive
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScheduleStatus {
+    pub active: Option<ColorScheme>, // Half of the day it is now, None when disabled
+    pub next_switch: Option<u64>,    // Unix timestamp
+    pub next_theme: Option<String>,
+    pub runner: Option<String>,      // "systemd" or "autostart"
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallScope {
+    #[default]
+    User,   // Installed into the user's home directory
+    System, // Installed into /usr/share through the privileged helper
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InstalledTheme {
+    pub name: String,
+    pub scope: InstallScope,
+    pub components: Vec<String>, // "theme", "icons", "cursors", "fonts"
+    pub paths: Vec<String>,      // Directories created by the install
+    pub installed_at: u64,       // Unix timestamp
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ThemeFonts {
+    pub interface: Option<String>, // Family names, matched against the bundled fonts
+    pub document: Option<String>,
+    pub monospace: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FontAxis {
+    pub tag: String, // e.g. "wght", "wdth"
+    pub min: f32,
+    pub default: f32,
+    pub max: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FontFamily {
+    pub name: String,
+    pub styles: Vec<String>,
+    pub files: Vec<String>,
+    pub monospace: bool,
+    pub axes: Vec<FontAxis>, // Empty unless the family has a variable font
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FontCollision {
+    pub family: String,
+    pub style: String,
+    pub installed_path: String, // Font already known to fontconfig with the same family and style
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FontScan {
+    pub families: Vec<FontFamily>,
+    pub collisions: Vec<FontCollision>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CursorReport {
+    pub valid: bool,          // At least one cursor parsed and none of the standard ones are missing
+    pub cursors: usize,       // Number of valid cursor files
+    pub sizes: Vec<u32>,      // Nominal sizes available across the theme
+    pub invalid: Vec<String>, // Files in cursors/ that aren't valid Xcursor files
+    pub missing: Vec<String>, // Standard cursors the theme doesn't provide
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictResolution {
+    Replace, // Overwrite the existing theme
+    Rename,  // Install under a suffixed name
+    Abort,   // Leave everything as it is
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ThemeConflict {
+    pub component: String,
+    pub path: String,      // Existing directory with the same name
+    pub managed: bool,     // Installed by reskin, as opposed to the distro or by hand
+    pub replaceable: bool, // False when the install scope can't overwrite it
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallStatus {
+    Installed,
+    Conflict, // Nothing was installed, a resolution is needed
+    Aborted,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InstallResult {
+    pub status: InstallStatus,
+    pub theme_name: String, // Final name, differs from the bundle when renamed
+    pub message: String,
+    pub conflicts: Vec<ThemeConflict>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ProgressEvent {
+    pub job_id: Option<u64>,
+    pub operation: String, // "bundle", "extract", "install" or "download"
+    pub phase: String,
+    pub bytes_done: u64,
+    pub bytes_total: u64, // 0 when unknown
+    pub files_done: u64,
+    pub files_total: u64, // 0 when unknown
+    pub current_file: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JobInfo {
+    pub id: u64,
+    pub operation: String,
+    pub description: String, // Theme or file the job works on
+    pub state: JobState,
+    pub error: Option<String>,
+    pub progress: Option<ProgressEvent>, // Last progress reported by the job
+    pub started_at: u64,                 // Unix timestamp
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OpenedTheme {
+    pub path: String,
+    pub manifest: Option<ThemeManifest>,
+    pub error: Option<String>, // Set when the file couldn't be read as a bundle
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LinkPreview {
+    pub link: String,
+    pub source: String, // Marketplace theme id or bundle URL
+    pub manifest: ThemeManifest,
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ThemeDependency {
+    pub id: String,              // Theme name as in the registry and marketplace
+    #[serde(default)]
+    pub version: Option<String>, // Range such as "^1.2", ">=1.0, <2" or "*"
+    #[serde(default)]
+    pub source: Option<String>,  // "marketplace" (default) or an https:// bundle URL
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PlanStep {
+    pub id: String,
+    pub version: Option<String>,
+    pub action: String,      // "installed" when already satisfied, otherwise "install"
+    pub source: String,      // "user", "system", "marketplace" or the bundle URL
+    pub required_by: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MissingDependency {
+    pub id: String,
+    pub version: Option<String>,
+    pub required_by: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct DependencyPlan {
+    pub steps: Vec<PlanStep>, // In install order, dependencies first
+    pub missing: Vec<MissingDependency>,
+    pub cycles: Vec<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SystemTheme {
+    pub id: String,              // Directory name, what settings refer to
+    pub name: String,            // Name= from index.theme, falls back to the id
+    pub path: String,
+    pub scope: InstallScope,     // Home directory or /usr/share
+    pub components: Vec<String>, // "theme", "shell", "icons", "cursors"
+    pub managed: bool,           // Installed by reskin
+    pub applied: Vec<String>,    // Components it is currently set as
+    pub preview: Option<String>, // Shipped or cached preview image, see get_theme_preview
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PaletteColor {
+    pub name: String,
+    pub expression: String,    // As written after @define-color
+    pub value: Option<String>, // Resolved #rrggbb or #rrggbbaa, None if it couldn't be resolved
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct PaletteVariant {
+    pub files: Vec<String>,                        // Stylesheets read, imports included
+    pub colors: Vec<PaletteColor>,                 // Every @define-color in declaration order
+    pub named: std::collections::BTreeMap<String, String>, // libadwaita names, filled from GTK 3 names when missing
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ThemePalette {
+    pub light: Option<PaletteVariant>,
+    pub dark: Option<PaletteVariant>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ComponentStatus {
+    Applied,
+    Failed,
+    Unsupported, // The desktop has no such setting
+    Skipped,     // The theme doesn't ship this component
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ComponentReport {
+    pub component: String,
+    pub status: ComponentStatus,
+    pub message: Option<String>, // Error for failed components
+}
+
+// Appearance settings as a backend read them, keyed by component
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct AppearanceSnapshot {
+    pub desktop: String,
+    pub values: std::collections::BTreeMap<String, String>, // "gtk", "icons", "cursor", "font_interface", ...
+    // Config files reskin writes outside the desktop's settings, None when the file didn't exist
+    #[serde(default)]
+    pub files: std::collections::BTreeMap<String, Option<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ApplyReport {
+    pub message: String,
+    pub components: Vec<ComponentReport>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SnapshotEntry {
+    pub id: u64,
+    pub label: String,   // What was about to change, e.g. "Before applying Nordic"
+    pub created_at: u64, // Unix timestamp
+    pub snapshot: AppearanceSnapshot,
+}
@@ -1,55 +1,67 @@
 use std::fs;
 use std::path::PathBuf;
 use dirs::home_dir;
+use crate::fonts::find_font_files;
+use crate::paths::{icons_dir, fonts_dir};
+use crate::progress::Progress;
 
-pub fn install_icons(staging_dir: &str, theme_name: &str, home_dir: &str) -> Result<(), String> {
-    let icons_dir = format!("{}/.local/share/icons", home_dir);
-    let dest_dir = format!("{}/{}", icons_dir, theme_name);
+pub fn install_icons(staging_dir: &str, theme_name: &str, progress: &mut Progress) -> Result<(), String> {
+    let icons_dir = icons_dir()?;
+    let dest_dir = icons_dir.join(theme_name);
     
     fs::create_dir_all(&icons_dir).map_err(|e| format!("Failed to create icons directory: {}", e))?;
         
-    if std::path::Path::new(&dest_dir).exists() {
+    if dest_dir.exists() {
         fs::remove_dir_all(&dest_dir).map_err(|e| format!("Failed to remove existing icons: {}", e))?;
     }
+    // Tracked only now, an existing directory would have been skipped
+    progress.track_cleanup(dest_dir.clone());
     
-    copy_dir_recursive(staging_dir, &dest_dir).map_err(|e| format!("Failed to install icons: {}", e))?;
+    copy_dir_with_progress(staging_dir, &dest_dir.to_string_lossy(), progress).map_err(|e| format!("Failed to install icons: {}", e))?;
     Ok(())
 }
 
-pub fn install_cursors(staging_dir: &str, theme_name: &str, home_dir: &str) -> Result<(), String> {
-    let cursors_dir = format!("{}/.local/share/icons", home_dir);
-    let dest_dir = format!("{}/{}", cursors_dir, theme_name);
+pub fn install_cursors(staging_dir: &str, theme_name: &str, progress: &mut Progress) -> Result<(), String> {
+    let cursors_dir = icons_dir()?;
+    let dest_dir = cursors_dir.join(theme_name);
     
     fs::create_dir_all(&cursors_dir).map_err(|e| format!("Failed to create cursors directory: {}", e))?;
         
-    if std::path::Path::new(&dest_dir).exists() {
+    if dest_dir.exists() {
         fs::remove_dir_all(&dest_dir).map_err(|e| format!("Failed to remove existing cursors: {}", e))?;
     }
+    progress.track_cleanup(dest_dir.clone());
     
-    copy_dir_recursive(staging_dir, &dest_dir).map_err(|e| format!("Failed to install cursors: {}", e))?;
+    copy_dir_with_progress(staging_dir, &dest_dir.to_string_lossy(), progress).map_err(|e| format!("Failed to install cursors: {}", e))?;
     Ok(())
 }
 
-pub fn install_fonts(staging_dir: &str, theme_name: &str, home_dir: &str) -> Result<(), String> {
-    let fonts_dir = format!("{}/.local/share/fonts/{}", home_dir, theme_name);
+pub fn install_fonts(staging_dir: &str, theme_name: &str, progress: &mut Progress) -> Result<(), String> {
+    let fonts_dir = fonts_dir()?.join(theme_name);
     fs::create_dir_all(&fonts_dir).map_err(|e| format!("Failed to create fonts directory: {}", e))?;
         
-    if let Ok(entries) = fs::read_dir(staging_dir) {
-        for entry in entries.flatten() {
-            if let Some(ext) = entry.path().extension() {
-                if ["ttf", "otf", "woff", "woff2", "eot"].contains(&ext.to_str().unwrap_or("")) {
-                    let dest_path = std::path::Path::new(&fonts_dir).join(entry.file_name());
-                    fs::copy(entry.path(), dest_path).map_err(|e| format!("Failed to copy font: {}", e))?;
-                }
-            }
+    // Keep the theme's folder layout, fontconfig scans font directories recursively
+    for font in find_font_files(std::path::Path::new(staging_dir)) {
+        let relative = font.strip_prefix(staging_dir).map_err(|e| format!("Invalid font path: {}", e))?;
+        let dest_path = fonts_dir.join(relative);
+        if let Some(parent) = dest_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create fonts directory: {}", e))?;
         }
+        progress.check_cancelled()?;
+        progress.set_file(&relative.to_string_lossy());
+        let bytes = fs::copy(&font, dest_path).map_err(|e| format!("Failed to copy font: {}", e))?;
+        progress.add(bytes, 1);
     }
     
     Ok(())
 }
 
-#[allow(non_snake_case)]
 pub fn copy_dir_recursive(src: &str, dst: &str) -> Result<(), std::io::Error> {
+    copy_dir_with_progress(src, dst, &mut Progress::none())
+}
+
+#[allow(non_snake_case)]
+pub fn copy_dir_with_progress(src: &str, dst: &str, progress: &mut Progress) -> Result<(), std::io::Error> {
     use std::path::Path;
     
     fs::create_dir_all(dst)?;
@@ -60,15 +72,38 @@ pub fn copy_dir_recursive(src: &str, dst: &str) -> Result<(), std::io::Error> {
         let dst_path = Path::new(dst).join(entry.file_name());
         
         if srcPath.is_dir() {
-            copy_dir_recursive(&srcPath.to_string_lossy(), &dst_path.to_string_lossy())?;
+            copy_dir_with_progress(&srcPath.to_string_lossy(), &dst_path.to_string_lossy(), progress)?;
         } else {
-            fs::copy(&srcPath, &dst_path)?;
+            progress.check_cancelled().map_err(std::io::Error::other)?;
+            progress.set_file(&srcPath.to_string_lossy());
+            let bytes = fs::copy(&srcPath, &dst_path)?;
+            progress.add(bytes, 1);
         }
     }
     
     Ok(())
 }
 
+// Total size and file count of a directory tree, used as progress totals
+pub fn dir_size(path: &std::path::Path) -> (u64, u64) {
+    let mut bytes = 0;
+    let mut files = 0;
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            let entry_path = entry.path();
+            if entry_path.is_dir() {
+                let (dir_bytes, dir_files) = dir_size(&entry_path);
+                bytes += dir_bytes;
+                files += dir_files;
+            } else if let Ok(meta) = entry.metadata() {
+                bytes += meta.len();
+                files += 1;
+            }
+        }
+    }
+    (bytes, files)
+}
+
 #[tauri::command]
 pub fn apply_config_file(file_data: Vec<u8>, file_name: String, dest_path: String) -> Result<String, String> {
     let path = if dest_path.starts_with("~") {
@@ -7,6 +7,7 @@ import SideNav from "./SideNav";
 import Titlebar from "./Titlebar";
 import React, { useEffect, useState, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import ThemeDetails from "./ThemeDetails";
 import Settings from "./Settings";
 import UploadTheme from "./UploadTheme";
@@ -18,7 +19,50 @@ export default function App(props) {
   const [installedThemes, setInstalledThemes] = useState([]);
   const [selectedTheme, setSelectedTheme] = useState(null);
   const [user, setUser] = useState(null);
+  const [openedTheme, setOpenedTheme] = useState(null);
+  const [deepLink, setDeepLink] = useState(null);
 
+  // .reskin files opened from the file manager or handed over by a second launch
+  useEffect(() => {
+	const takePending = async () => {
+	  try {
+		const opened = await invoke("take_pending_open");
+		if (opened) {
+		  setOpenedTheme(opened);
+		  setCurrentView('installer');
+		}
+	  } catch {}
+	};
+	// reskin:// links from web pages, confirmed in the installer before anything is written
+	const takePendingLink = async () => {
+	  try {
+		const link = await invoke("take_pending_link");
+		if (link) {
+		  setDeepLink(link);
+		  setCurrentView('installer');
+		}
+	  } catch {}
+	};
+	takePending();
+	takePendingLink();
+	const unlisten = listen("open-theme", takePending);
+	const unlistenLink = listen("open-link", takePendingLink);
+	return () => {
+	  unlisten.then((fn) => fn());
+	  unlistenLink.then((fn) => fn());
+	};
+  }, []);
+
+
+  // The install location used to be a localStorage path the backend never read, move it over once
+  useEffect(() => {
+	const oldLocation = localStorage.getItem("reskin_install_location");
+	if (oldLocation === null) return;
+	const location = oldLocation.includes(".local/share") ? "xdg" : "default";
+	invoke("set_install_location", { location })
+	  .then(() => localStorage.removeItem("reskin_install_location"))
+	  .catch((err) => console.error("Failed to migrate install location:", err));
+  }, []);
 
   // Get recently installed themes from localStorage
   useEffect(() => {
@@ -80,6 +124,9 @@ export default function App(props) {
 	case 'installer':
 	  return (
 		<ThemeInstaller
+		  key={deepLink || openedTheme?.path}
+		  openedTheme={openedTheme}
+		  deepLink={deepLink}
 		  onThemeInstalled={addToRecentlyInstalled}
 		  setCurrentView={setCurrentView}
 		/>
@@ -7,7 +7,7 @@ import { getTranslationObject } from "./locales/index.js";
 // Pre-defined config types
 const configMap = {
   gtk: "~/.config/gtk-3.0/settings.ini",
-  kvantum: "~/.config/Kvantum/Kvantum.kvconfig",
+  kvantum: "~/.config/Kvantum/kvantum.kvconfig",
   kitty: "~/.config/kitty/kitty.yml",
   waybar: "~/.config/waybar/config.jsonc",
   bashrc: "~/.bashrc",
@@ -5,20 +5,25 @@ import "./Settings.css";
 import { getTranslationObject, getLanguageOptions } from "./locales/index.js";
 
 export default function Settings() {
-    const [installLocation, setInstallLocation] = useState(
-        localStorage.getItem("reskin_install_location") || "~/.themes"
-    );
+    const [installLocation, setInstallLocation] = useState("default");
     const [autoApply, setAutoApply] = useState(
         localStorage.getItem("reskin_auto_apply") === "true"
     );
     const [backupConfig, setBackupConfig] = useState(
         localStorage.getItem("reskin_backup_config") === "true"
     );
+    const [flatpakIntegration, setFlatpakIntegration] = useState(false);
+    const [libadwaitaTheming, setLibadwaitaTheming] = useState(false);
+    const [qtColorSchemes, setQtColorSchemes] = useState(false);
+    const [qtEnvironment, setQtEnvironment] = useState(false);
     const [language, setLanguage] = useState(
         localStorage.getItem("reskin_language") || "en"
     );
     const [appVersion, setAppVersion] = useState("Unknown");
     const [fade, setFade] = useState(false);
+    const [snapshots, setSnapshots] = useState([]);
+    const [schedule, setSchedule] = useState(null);
+    const [scheduleStatus, setScheduleStatus] = useState(null);
 
     const t = getTranslationObject(language);
     const languageOptions = getLanguageOptions();
@@ -34,11 +39,104 @@ export default function Settings() {
             }
         };
         getVersion();
+
+        // The backend owns the install location, read it from there
+        invoke("get_install_location")
+            .then((location) => setInstallLocation(location))
+            .catch((err) => console.error("Failed to get install location:", err));
+        invoke("get_flatpak_integration")
+            .then((enabled) => setFlatpakIntegration(enabled))
+            .catch((err) => console.error("Failed to get Flatpak integration:", err));
+        invoke("get_libadwaita_theming")
+            .then((enabled) => setLibadwaitaTheming(enabled))
+            .catch((err) => console.error("Failed to get libadwaita theming:", err));
+        invoke("get_qt_color_schemes")
+            .then((enabled) => setQtColorSchemes(enabled))
+            .catch((err) => console.error("Failed to get Qt color schemes:", err));
+        invoke("get_qt_environment")
+            .then((enabled) => setQtEnvironment(enabled))
+            .catch((err) => console.error("Failed to get Qt environment:", err));
+        loadSnapshots();
+        invoke("get_schedule")
+            .then((config) => setSchedule(config))
+            .catch((err) => console.error("Failed to get schedule:", err));
+        invoke("get_schedule_status")
+            .then((status) => setScheduleStatus(status))
+            .catch((err) => console.error("Failed to get schedule status:", err));
     }, []);
 
-    useEffect(() => {
-        localStorage.setItem("reskin_install_location", installLocation);
-    }, [installLocation]);
+    const updateSchedule = (changes) => setSchedule((prev) => ({ ...prev, ...changes }));
+
+    const saveSchedule = async () => {
+        try {
+            const status = await invoke("configure_schedule", { config: schedule });
+            setScheduleStatus(status);
+        } catch (err) {
+            alert(`${t.settings.status["status.schedule_failure"]}\n${err}`);
+        }
+    };
+
+    const changeLibadwaitaTheming = async (enabled) => {
+        try {
+            await invoke("set_libadwaita_theming", { enabled });
+            setLibadwaitaTheming(enabled);
+        } catch (err) {
+            console.error("Failed to set libadwaita theming:", err);
+        }
+    };
+
+    const changeQtColorSchemes = async (enabled) => {
+        try {
+            await invoke("set_qt_color_schemes", { enabled });
+            setQtColorSchemes(enabled);
+        } catch (err) {
+            console.error("Failed to set Qt color schemes:", err);
+        }
+    };
+
+    const changeQtEnvironment = async (enabled) => {
+        try {
+            await invoke("set_qt_environment", { enabled });
+            setQtEnvironment(enabled);
+        } catch (err) {
+            console.error("Failed to set Qt environment:", err);
+        }
+    };
+
+    const loadSnapshots = () => {
+        invoke("list_snapshots")
+            .then((list) => setSnapshots(list))
+            .catch((err) => console.error("Failed to list snapshots:", err));
+    };
+
+    // Restoring and undoing both report per component, show the summary
+    const runRestore = async (command, args) => {
+        try {
+            const result = await invoke(command, args);
+            alert(result.message);
+        } catch (err) {
+            alert(`${t.settings.status["status.restore_failure"]}\n${err}`);
+        }
+        loadSnapshots();
+    };
+
+    const changeInstallLocation = async (location) => {
+        try {
+            await invoke("set_install_location", { location });
+            setInstallLocation(location);
+        } catch (err) {
+            console.error("Failed to set install location:", err);
+        }
+    };
+
+    const changeFlatpakIntegration = async (enabled) => {
+        try {
+            await invoke("set_flatpak_integration", { enabled });
+            setFlatpakIntegration(enabled);
+        } catch (err) {
+            console.error("Failed to set Flatpak integration:", err);
+        }
+    };
 
     useEffect(() => {
         localStorage.setItem("reskin_auto_apply", autoApply.toString());
@@ -61,12 +159,16 @@ export default function Settings() {
                     <label htmlFor="installLocation" title={t.settings.tooltip["tooltip.install_location"]}>
                         {t.settings.label["label.install_location"]}
                     </label>
-                    <input
+                    <select
                         id="installLocation"
-                        type="text"
                         value={installLocation}
-                        onChange={(e) => setInstallLocation(e.target.value)}
-                    />
+                        onChange={(e) => changeInstallLocation(e.target.value)}
+                        style={{ color: "black" }}
+                    >
+                        <option value="default">{t.settings.option["option.install_location_default"]}</option>
+                        <option value="legacy">{t.settings.option["option.install_location_legacy"]}</option>
+                        <option value="xdg">{t.settings.option["option.install_location_xdg"]}</option>
+                    </select>
                 </div>
                 <div className="settings-row">
                     <label htmlFor="autoApply" title={t.settings.tooltip["tooltip.auto_apply"]}>
@@ -90,6 +192,50 @@ export default function Settings() {
                         onChange={(e) => setBackupConfig(e.target.checked)}
                     />
                 </div>
+                <div className="settings-row">
+                    <label htmlFor="flatpakIntegration" title={t.settings.tooltip["tooltip.flatpak_integration"]}>
+                        {t.settings.label["label.flatpak_integration"]}
+                    </label>
+                    <input
+                        id="flatpakIntegration"
+                        type="checkbox"
+                        checked={flatpakIntegration}
+                        onChange={(e) => changeFlatpakIntegration(e.target.checked)}
+                    />
+                </div>
+                <div className="settings-row">
+                    <label htmlFor="libadwaitaTheming" title={t.settings.tooltip["tooltip.libadwaita_theming"]}>
+                        {t.settings.label["label.libadwaita_theming"]}
+                    </label>
+                    <input
+                        id="libadwaitaTheming"
+                        type="checkbox"
+                        checked={libadwaitaTheming}
+                        onChange={(e) => changeLibadwaitaTheming(e.target.checked)}
+                    />
+                </div>
+                <div className="settings-row">
+                    <label htmlFor="qtColorSchemes" title={t.settings.tooltip["tooltip.qt_color_schemes"]}>
+                        {t.settings.label["label.qt_color_schemes"]}
+                    </label>
+                    <input
+                        id="qtColorSchemes"
+                        type="checkbox"
+                        checked={qtColorSchemes}
+                        onChange={(e) => changeQtColorSchemes(e.target.checked)}
+                    />
+                </div>
+                <div className="settings-row">
+                    <label htmlFor="qtEnvironment" title={t.settings.tooltip["tooltip.qt_environment"]}>
+                        {t.settings.label["label.qt_environment"]}
+                    </label>
+                    <input
+                        id="qtEnvironment"
+                        type="checkbox"
+                        checked={qtEnvironment}
+                        onChange={(e) => changeQtEnvironment(e.target.checked)}
+                    />
+                </div>
                 <div className="settings-row">
                     <label htmlFor="language" title={t.settings.tooltip["tooltip.language"]}>
                         {t.settings.label["label.language"]}
@@ -108,6 +254,126 @@ export default function Settings() {
                     </select>
                 </div>
             </div>
+            {schedule && (
+                <div className="settings-section">
+                    <h3>{t.settings.section["section.schedule"]}</h3>
+                    <div className="settings-row">
+                        <label htmlFor="scheduleEnabled">{t.settings.label["label.schedule_enabled"]}</label>
+                        <input
+                            id="scheduleEnabled"
+                            type="checkbox"
+                            checked={schedule.enabled}
+                            onChange={(e) => updateSchedule({ enabled: e.target.checked })}
+                        />
+                    </div>
+                    <div className="settings-row">
+                        <label htmlFor="lightTheme">{t.settings.label["label.light_theme"]}</label>
+                        <input
+                            id="lightTheme"
+                            value={schedule.light_theme}
+                            onChange={(e) => updateSchedule({ light_theme: e.target.value })}
+                            style={{ color: "black" }}
+                        />
+                    </div>
+                    <div className="settings-row">
+                        <label htmlFor="darkTheme">{t.settings.label["label.dark_theme"]}</label>
+                        <input
+                            id="darkTheme"
+                            value={schedule.dark_theme}
+                            onChange={(e) => updateSchedule({ dark_theme: e.target.value })}
+                            style={{ color: "black" }}
+                        />
+                    </div>
+                    <div className="settings-row">
+                        <label htmlFor="scheduleMode">{t.settings.label["label.schedule_mode"]}</label>
+                        <select
+                            id="scheduleMode"
+                            value={schedule.mode}
+                            onChange={(e) => updateSchedule({ mode: e.target.value })}
+                            style={{ color: "black" }}
+                        >
+                            <option value="fixed">{t.settings.option["option.schedule_fixed"]}</option>
+                            <option value="sun">{t.settings.option["option.schedule_sun"]}</option>
+                        </select>
+                    </div>
+                    {schedule.mode === "fixed" ? (
+                        <div className="settings-row">
+                            <label>{t.settings.label["label.schedule_times"]}</label>
+                            <span>
+                                <input
+                                    type="time"
+                                    value={schedule.light_time}
+                                    onChange={(e) => updateSchedule({ light_time: e.target.value })}
+                                    style={{ color: "black" }}
+                                />
+                                {" / "}
+                                <input
+                                    type="time"
+                                    value={schedule.dark_time}
+                                    onChange={(e) => updateSchedule({ dark_time: e.target.value })}
+                                    style={{ color: "black" }}
+                                />
+                            </span>
+                        </div>
+                    ) : (
+                        <div className="settings-row">
+                            <label>{t.settings.label["label.schedule_location"]}</label>
+                            <span>
+                                <input
+                                    type="number"
+                                    step="0.01"
+                                    value={schedule.latitude}
+                                    onChange={(e) => updateSchedule({ latitude: parseFloat(e.target.value) || 0 })}
+                                    style={{ color: "black", width: "90px" }}
+                                />
+                                {" "}
+                                <input
+                                    type="number"
+                                    step="0.01"
+                                    value={schedule.longitude}
+                                    onChange={(e) => updateSchedule({ longitude: parseFloat(e.target.value) || 0 })}
+                                    style={{ color: "black", width: "90px" }}
+                                />
+                            </span>
+                        </div>
+                    )}
+                    <div className="settings-row">
+                        <span>
+                            {scheduleStatus?.next_switch
+                                ? `${t.settings.label["label.next_switch"]} ${new Date(scheduleStatus.next_switch * 1000).toLocaleString()} (${scheduleStatus.next_theme})`
+                                : t.settings.label["label.no_switch"]}
+                        </span>
+                        <button onClick={saveSchedule}>{t.settings.button["button.save"]}</button>
+                    </div>
+                </div>
+            )}
+            <div className="settings-section">
+                <h3>{t.settings.section["section.history"]}</h3>
+                <div className="settings-row">
+                    <span>{t.settings.label["label.undo_apply"]}</span>
+                    <button
+                        disabled={snapshots.length === 0}
+                        onClick={() => runRestore("undo_last_apply")}
+                    >
+                        {t.settings.button["button.undo"]}
+                    </button>
+                </div>
+                {snapshots.length === 0 && (
+                    <div className="settings-row">
+                        <span>{t.settings.label["label.no_snapshots"]}</span>
+                    </div>
+                )}
+                {snapshots.map((snapshot) => (
+                    <div className="settings-row" key={snapshot.id}>
+                        <span>
+                            {snapshot.label} ({snapshot.snapshot.desktop}, {new Date(snapshot.created_at * 1000).toLocaleString()})
+                        </span>
+                        <button onClick={() => runRestore("restore_snapshot", { id: snapshot.id })}>
+                            {t.settings.button["button.restore"]}
+                        </button>
+                    </div>
+                ))}
+            </div>
             <div className="settings-section">
                 <h3>{t.settings.section["section.about"]}</h3>
                 <div className="settings-row">
@@ -155,6 +155,8 @@ export default function ThemeBundler() {
         theme_directory: selectedFolder.path,
         assets: fileData.map(f => f.path),
         output_path: outputPath,
+        // Themes without a preview.png get a rendered one
+        generate_preview: true,
       };
 
       let result;
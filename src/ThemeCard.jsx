@@ -2,7 +2,7 @@
 import React, { useEffect, useState } from "react";
 import "./ThemeCard.css";
 import { getTranslationObject } from "./locales/index.js";
-import "@tauri-apps/api/core";
+import { invoke } from "@tauri-apps/api/core";
 
 export default function ThemeCard({ theme, onClick }) {
   // Use stored language or fallback to English
@@ -10,6 +10,25 @@ export default function ThemeCard({ theme, onClick }) {
   const t = getTranslationObject(language);
 
   const [missing, setMissing] = useState(false);
+  const [renderedPreview, setRenderedPreview] = useState(null);
+
+  // Installed themes have no preview URL, use the one they shipped or render one
+  useEffect(() => {
+    if (theme.preview || !window.__TAURI__) return;
+    let url = null;
+    let cancelled = false;
+    invoke("get_theme_preview", { themeName: theme.name })
+      .then((png) => {
+        if (cancelled) return;
+        url = URL.createObjectURL(new Blob([png], { type: "image/png" }));
+        setRenderedPreview(url);
+      })
+      .catch(() => {});
+    return () => {
+      cancelled = true;
+      if (url) URL.revokeObjectURL(url);
+    };
+  }, [theme.name, theme.preview]);
 
   useEffect(() => {
     // Only check for recently viewed themes in Tauri
@@ -34,7 +53,7 @@ export default function ThemeCard({ theme, onClick }) {
       style={{ cursor: "pointer", opacity: missing ? 0.5 : 1 }}
     >
       <img
-        src={theme.preview}
+        src={theme.preview || renderedPreview || "/default-preview.png"}
         alt={theme.name || t.themeCard.preview_alt}
         onError={e => { e.target.onerror = null; e.target.src = "/default-preview.png"; }}
         style={{ opacity: missing ? 0.5 : 1 }}
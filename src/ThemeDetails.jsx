@@ -18,6 +18,7 @@ export default function ThemeDetails({ theme, onBack }) {
 
   const [manifest, setManifest] = useState(theme);
   const [isInstalled, setIsInstalled] = useState(false);
+  const [components, setComponents] = useState(["theme"]);
   const databaseId = "reskin";
   const collectionId = "reports";
 
@@ -34,7 +35,22 @@ export default function ThemeDetails({ theme, onBack }) {
         setIsInstalled(false);
       }
     }
-    if (theme && theme.name) checkIfInstalled();
+    // Start from what the install recorded, themes installed by hand are treated as GTK themes
+    async function loadInstalledComponents() {
+      try {
+        const installed = await invoke('get_installed_themes');
+        const recorded = installed
+          .filter((entry) => entry.name === theme.name)
+          .flatMap((entry) => entry.components);
+        setComponents(recorded.length > 0 ? [...new Set(recorded)] : ["theme"]);
+      } catch {
+        setComponents(["theme"]);
+      }
+    }
+    if (theme && theme.name) {
+      checkIfInstalled();
+      loadInstalledComponents();
+    }
   }, [theme]);
 
   if (!manifest) return <div style={{ padding: 40 }}>{t.themedetails.status["loading"]}</div>;
@@ -42,12 +58,20 @@ export default function ThemeDetails({ theme, onBack }) {
   const handleApply = async () => {
     if (!manifest || !manifest.name) return;
     try {
-      await invoke('apply_theme', { themeName: manifest.name });
+      const result = await invoke('apply_theme', { themeName: manifest.name, components });
+      alert(result.message);
     } catch (e) {
       console.error('Apply Theme error:', e);
+      alert(`${t.themedetails.status["status.apply_failure"]}\n${e}`);
     }
   };
 
+  const toggleComponent = (component) => {
+    setComponents(prev => prev.includes(component)
+      ? prev.filter(c => c !== component)
+      : [...prev, component]);
+  };
+
   const handleInstall = async () => {
     if (!manifest || !manifest.file) return;
     try {
@@ -115,6 +139,20 @@ export default function ThemeDetails({ theme, onBack }) {
           {manifest.name && <h1 style={{ margin: 0 }}>{manifest.name}</h1>}
           {manifest.author && <h2 style={{ margin: "8px 0 16px 0", fontWeight: 400 }}>{t.themedetails.label["label.author_prefix"]} {manifest.author}</h2>}
           {manifest.description && <p style={{ maxWidth: "400px" }}>{manifest.description}</p>}
+          {isInstalled && (
+            <div style={{ display: "flex", gap: "16px", marginTop: "12px" }}>
+              {["theme", "icons", "cursors", "fonts", "kde", "qt"].map(component => (
+                <label key={component}>
+                  <input
+                    type="checkbox"
+                    checked={components.includes(component)}
+                    onChange={() => toggleComponent(component)}
+                  />
+                  {" "}{t.themedetails.component[`component.${component}`]}
+                </label>
+              ))}
+            </div>
+          )}
           <button
             style={{ marginTop: "18px", padding: "10px 24px", border: "none", borderRadius: "8px", fontWeight: "bold", fontSize: "1rem", cursor: "pointer" }}
             onClick={handleButtonAction}
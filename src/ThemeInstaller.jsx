@@ -1,9 +1,10 @@
 import React, { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import "./ThemeInstaller.css";
 import { getTranslationObject } from "./locales/index.js";
 
-export default function ThemeInstaller({ onThemeInstalled }) {
+export default function ThemeInstaller({ onThemeInstalled, openedTheme, deepLink }) {
   const language = localStorage.getItem("reskin_language") || "en";
   const t = getTranslationObject(language);
 
@@ -13,12 +14,50 @@ export default function ThemeInstaller({ onThemeInstalled }) {
   const [statusType, setStatusType] = useState("info");
   const [dragOver, setDragOver] = useState(false);
   const [isInstalling, setIsInstalling] = useState(false);
+  const [systemWide, setSystemWide] = useState(false);
+  const [conflicts, setConflicts] = useState([]);
+  const [progress, setProgress] = useState(null);
+
+  // Backend operations report progress as "progress" events
+  useEffect(() => {
+    const unlisten = listen("progress", (event) => {
+      const finished = ["done", "failed", "cancelled"].includes(event.payload.phase);
+      setProgress(finished ? null : event.payload);
+    });
+    return () => { unlisten.then((fn) => fn()); };
+  }, []);
 
   const showStatus = (msg, type = "info") => {
     setStatus(msg);
     setStatusType(type);
   };
 
+  // Preload a bundle that was opened from outside the app
+  useEffect(() => {
+    if (!openedTheme) return;
+    const name = openedTheme.path.split("/").pop();
+    setSelectedFile({ name, path: openedTheme.path });
+    if (openedTheme.manifest) {
+      setThemeInfo(openedTheme.manifest);
+      showStatus(t.themeinstaller.status.info_loaded, "success");
+    } else {
+      showStatus(t.themeinstaller.status.error_info_load, "error");
+    }
+  }, [openedTheme]);
+
+  // Links are downloaded into memory first so the manifest can be confirmed
+  useEffect(() => {
+    if (!deepLink) return;
+    showStatus(t.themeinstaller.status.link_loading, "info");
+    invoke("preview_deep_link", { link: deepLink })
+      .then((preview) => {
+        setSelectedFile({ name: `${preview.manifest.name}.reskin`, link: deepLink });
+        setThemeInfo(preview.manifest);
+        showStatus(t.themeinstaller.status.link_confirm.replace("{source}", preview.source), "info");
+      })
+      .catch((err) => showStatus(t.themeinstaller.status.link_failure.replace("{error}", err), "error"));
+  }, [deepLink]);
+
   const handleDragOver = (e) => { e.preventDefault(); setDragOver(true); };
   const handleDragLeave = (e) => { e.preventDefault(); setDragOver(false); };
 
@@ -33,31 +72,48 @@ export default function ThemeInstaller({ onThemeInstalled }) {
     }
   };
 
+  // Dropped files have no path in the webview, the bundle bytes go to the backend instead
   const handleFileSelected = async (file) => {
-    setSelectedFile(file);
     showStatus(t.themeinstaller.status.success_select.replace("{filePath}", file.name), "success");
 
-    // Load theme info
+    // Load theme info from the bundle header
     try {
-      const arrayBuffer = await file.arrayBuffer();
-      const text = new TextDecoder().decode(arrayBuffer);
-      const json = JSON.parse(text);
-      setThemeInfo(json);
+      const data = Array.from(new Uint8Array(await file.arrayBuffer()));
+      setSelectedFile({ name: file.name, data });
+      const manifest = await invoke("extract_theme_info", { fileData: data });
+      setThemeInfo(manifest);
       showStatus(t.themeinstaller.status.info_loaded, "success");
     } catch (err) {
       showStatus(t.themeinstaller.status.error_info_load, "error");
     }
   };
 
-  const handleInstall = async () => {
+  const handleInstall = async (resolution = null) => {
     if (!selectedFile) return showStatus(t.themeinstaller.status.error_no_theme_to_apply, "error");
     setIsInstalling(true);
+    setConflicts([]);
     showStatus(t.themeinstaller.status.installing, "info");
 
     try {
-      await invoke("install_reskin_theme", { filePath: selectedFile.path });
-      showStatus(t.themeinstaller.status.install_success, "success");
-      onThemeInstalled && onThemeInstalled(selectedFile);
+      const scope = systemWide ? "system" : "user";
+      let result;
+      if (selectedFile.link) {
+        result = await invoke("confirm_deep_link", { link: selectedFile.link, autoApply: false, scope, resolution });
+      } else if (selectedFile.data) {
+        result = await invoke("install_theme_from_data", { fileData: selectedFile.data, fileName: selectedFile.name, autoApply: false, scope, resolution });
+      } else {
+        result = await invoke("install_theme_from_file", { filePath: selectedFile.path, autoApply: false, scope, resolution });
+      }
+      if (result?.status === "conflict") {
+        // Let the user pick how to resolve the name clash
+        setConflicts(result.conflicts);
+        showStatus(t.themeinstaller.status.conflict.replace("{name}", result.theme_name), "error");
+      } else if (result?.status === "aborted") {
+        showStatus(t.themeinstaller.status.aborted, "info");
+      } else {
+        showStatus(t.themeinstaller.status.install_success, "success");
+        onThemeInstalled && onThemeInstalled({ ...themeInfo, name: result.theme_name });
+      }
     } catch (err) {
       showStatus(t.themeinstaller.status.install_failure.replace("{error.message || error}", err.message || err), "error");
     }
@@ -86,9 +142,45 @@ export default function ThemeInstaller({ onThemeInstalled }) {
           <div>{t.themeinstaller.info_preview.license} {themeInfo.license}</div>
         </div>
       )}
-      <button onClick={handleInstall} disabled={isInstalling}>
-        {t.themeinstaller.button.install}
-      </button>
+      <label className="system-wide">
+        <input type="checkbox" checked={systemWide} onChange={(e) => { setSystemWide(e.target.checked); setConflicts([]); }} />
+        {t.themeinstaller.option.system_wide}
+      </label>
+      {conflicts.length > 0 ? (
+        <div className="conflicts">
+          {conflicts.map((c) => (
+            <div key={c.path}>
+              {c.path} {c.managed ? t.themeinstaller.conflict.managed : t.themeinstaller.conflict.foreign}
+            </div>
+          ))}
+          {conflicts.some((c) => !c.replaceable) ? (
+            <p>{t.themeinstaller.conflict.system_foreign}</p>
+          ) : (
+            <button onClick={() => handleInstall("replace")} disabled={isInstalling}>{t.themeinstaller.conflict.replace}</button>
+          )}
+          <button onClick={() => handleInstall("rename")} disabled={isInstalling}>{t.themeinstaller.conflict.rename}</button>
+          <button onClick={() => handleInstall("abort")} disabled={isInstalling}>{t.themeinstaller.conflict.abort}</button>
+        </div>
+      ) : (
+        <button onClick={() => handleInstall()} disabled={isInstalling}>
+          {t.themeinstaller.button.install}
+        </button>
+      )}
+      {progress && (
+        <div className="progress">
+          <div>{progress.phase}{progress.current_file ? `: ${progress.current_file}` : ""}</div>
+          <progress
+            value={progress.bytes_total ? progress.bytes_done : undefined}
+            max={progress.bytes_total || undefined}
+          />
+          {progress.files_total > 0 && <div>{progress.files_done} / {progress.files_total}</div>}
+          {progress.job_id && (
+            <button onClick={() => invoke("cancel_job", { id: progress.job_id }).catch(() => {})}>
+              {t.themeinstaller.button.cancel}
+            </button>
+          )}
+        </div>
+      )}
       <div className={`status ${statusType}`}>{status}</div>
     </div>
   );